@@ -0,0 +1,565 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::lexer::{Import, Position};
+
+// Whole program : every function of the main file and of its imports
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub functions: Vec<Function>,
+    pub imports: Vec<Import>,
+    pub node_count: usize, // statements and expressions, their ids are below it
+}
+
+impl Program {
+    // number the statements and expressions of every function in source order
+    pub fn number_nodes(&mut self) {
+        let mut next = 0;
+        for function in &mut self.functions {
+            number_nodes(&mut function.body, &mut next);
+        }
+        self.node_count = next;
+    }
+
+    // find a function by name
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+
+    // all the functions, in declaration order
+    pub fn functions(&self) -> impl Iterator<Item = &Function> {
+        self.functions.iter()
+    }
+}
+
+// Function declaration, an empty body is valid and calling it does nothing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<Param>,
+    pub body: Block,
+    pub pos: Position,
+}
+
+impl Function {
+    pub fn param_count(&self) -> usize {
+        self.params.len()
+    }
+
+    // index of the parameter each argument of a call is given to
+    pub fn arg_order(&self, args: &[Arg]) -> Result<Vec<usize>, String> {
+        let mut order: Vec<usize> = Vec::new();
+        for (i, arg) in args.iter().enumerate() {
+            let index = match &arg.name {
+                None => i,
+                Some(name) => self
+                    .params
+                    .iter()
+                    .position(|p| p.name == *name)
+                    .ok_or_else(|| format!("function {} has no parameter {}", self.name, name))?,
+            };
+            if order.contains(&index) {
+                return Err(format!("parameter {} given twice", self.params[index].name));
+            }
+            order.push(index);
+        }
+        Ok(order)
+    }
+}
+
+// Argument of a call, a named one is given to the parameter of that name : call f(x: 1)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arg {
+    pub name: Option<String>,
+    pub value: Expr,
+}
+
+impl Arg {
+    pub fn to_sexpr(&self) -> String {
+        match &self.name {
+            Some(name) => format!("(arg {} {})", name, self.value.to_sexpr()),
+            None => self.value.to_sexpr(),
+        }
+    }
+}
+
+// Function parameter
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub ty: Type,
+    pub pos: Position,
+}
+
+// Type of a value, only int, float and arrays of them can be written in an annotation
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Int,
+    Float,
+    Str,
+    Bool,
+    Array(Box<Type>),
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Str => write!(f, "string"),
+            Type::Bool => write!(f, "bool"),
+            Type::Array(elem) => write!(f, "[{}]", elem),
+        }
+    }
+}
+
+// List of statements between braces
+pub type Block = Vec<Stmt>;
+
+// Identifier of a statement or an expression, given in source order once the program is parsed
+// so the passes can keep what they find about a node in a side table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct NodeId(pub usize);
+
+// Side table of a pass, a value for some of the nodes indexed by their id
+#[derive(Debug, Clone)]
+pub struct NodeMap<T> {
+    values: Vec<Option<T>>,
+}
+
+impl<T> Default for NodeMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> NodeMap<T> {
+    pub fn new() -> Self {
+        Self { values: Vec::new() }
+    }
+
+    pub fn insert(&mut self, id: NodeId, value: T) {
+        if id.0 >= self.values.len() {
+            self.values.resize_with(id.0 + 1, || None);
+        }
+        self.values[id.0] = Some(value);
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.values.get(id.0).and_then(Option::as_ref)
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    // keep only the nodes for which `keep` is true
+    pub fn retain(&mut self, mut keep: impl FnMut(NodeId, &T) -> bool) {
+        for (i, value) in self.values.iter_mut().enumerate() {
+            if value.as_ref().is_some_and(|v| !keep(NodeId(i), v)) {
+                *value = None;
+            }
+        }
+    }
+}
+
+// give the statements and expressions of a block the ids from `next` on, a statement before
+// its expressions
+pub fn number_nodes(block: &mut Block, next: &mut usize) {
+    for stmt in block {
+        stmt.number(next);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub pos: Position,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StmtKind {
+    // let x = expr / local x = expr, or let x: type without a value. A let is visible up to the
+    // end of its block. A local is visible from its declaration to the end of the function,
+    // after the blocks around it too, and declaring it again assigns it. Like any variable it is
+    // read only where it is assigned on every path, so a local declared in an if is usable
+    // after it when every branch declares it, and one declared in a loop body is not
+    // usable after the loop, which may not run
+    Let {
+        name: String,
+        ty: Option<Type>,
+        value: Option<Expr>,
+        local: bool,
+    },
+    // x = expr
+    Assign {
+        name: String,
+        value: Expr,
+    },
+    // print expr, expr... / println expr, expr..., the values are separated by a space, with an
+    // optional number of decimals for the floats among them : println x, y : 2
+    // eprint and eprintln write to stderr, println alone prints a blank line
+    Print {
+        args: Vec<Expr>,
+        newline: bool,
+        precision: Option<usize>,
+        to_stderr: bool,
+    },
+    // call name(args)
+    Call {
+        name: String,
+        args: Vec<Arg>,
+    },
+    // if cond { then } else { otherwise }, 'elif' and 'else if' both give a nested if in otherwise
+    If {
+        cond: Expr,
+        then: Block,
+        otherwise: Option<Block>,
+    },
+    // for var = start to end step expr { body }, without a step it counts by 1 towards end
+    For {
+        var: String,
+        start: Expr,
+        end: Expr,
+        step: Option<Expr>,
+        body: Block,
+    },
+    // while cond { body }
+    While {
+        cond: Expr,
+        body: Block,
+    },
+    // break, or break expr ; the values of the breaks of a loop have the same type, they are
+    // ignored until loops are expressions
+    Break(Option<Expr>),
+    Next,
+    // fn declared in a block, callable from that block only once declared. It doesn't see
+    // the variables of the enclosing function. Shared, so a checker or an interpreter keeps
+    // it once the block declaring it is gone
+    Fn(Rc<Function>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub pos: Position,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Integer(i32),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    Var(String),
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    ToStr(Box<Expr>),
+    // to_int(s) / to_float(s), parse a string, failing at runtime
+    ToInt(Box<Expr>),
+    ToFloat(Box<Expr>),
+    // [e1, e2, ...], the elements all have the same type, arrays for a matrix : [[1, 2], [3, 4]]
+    Array(Vec<Expr>),
+    // array[index], positioned at the '['
+    Index {
+        array: Box<Expr>,
+        index: Box<Expr>,
+    },
+    // len(array or string)
+    Len(Box<Expr>),
+    // min(a, b), max(a, b), abs(x), on numbers
+    Builtin {
+        name: Builtin,
+        args: Vec<Expr>,
+    },
+    // expr as type, float to int truncates, a float out of the int range overflows
+    Cast {
+        expr: Box<Expr>,
+        ty: Type,
+    },
+    // (expr : type), the value must already have the type
+    Ascription {
+        expr: Box<Expr>,
+        ty: Type,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    NotEq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+// Numeric function of the language, its name is not reserved and stays usable for a variable
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Builtin {
+    Min,
+    Max,
+    Abs,
+}
+
+impl Builtin {
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        match name {
+            "min" => Some(Builtin::Min),
+            "max" => Some(Builtin::Max),
+            "abs" => Some(Builtin::Abs),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Builtin::Min => "min",
+            Builtin::Max => "max",
+            Builtin::Abs => "abs",
+        }
+    }
+
+    // number of arguments
+    pub fn arity(&self) -> usize {
+        match self {
+            Builtin::Min | Builtin::Max => 2,
+            Builtin::Abs => 1,
+        }
+    }
+}
+
+impl UnaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            UnaryOp::Neg => "-",
+        }
+    }
+}
+
+impl BinaryOp {
+    // operator as written in the source
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Eq => "==",
+            BinaryOp::NotEq => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        }
+    }
+}
+
+// S-expression rendering of the AST, positions are left out : (fn main () (print (+ 1 2)))
+
+impl Program {
+    // one line per function, in declaration order
+    pub fn to_sexpr(&self) -> String {
+        self.functions().map(Function::to_sexpr).collect::<Vec<_>>().join("\n")
+    }
+}
+
+impl Function {
+    pub fn to_sexpr(&self) -> String {
+        let params: Vec<String> = self.params.iter().map(|p| format!("({} {})", p.name, p.ty)).collect();
+        let mut s = format!("(fn {} ({})", self.name, params.join(" "));
+        for stmt in &self.body {
+            s.push(' ');
+            s.push_str(&stmt.to_sexpr());
+        }
+        s.push(')');
+        s
+    }
+}
+
+// statements of a block between parentheses : (s1 s2)
+fn block_sexpr(block: &Block) -> String {
+    let stmts: Vec<String> = block.iter().map(Stmt::to_sexpr).collect();
+    format!("({})", stmts.join(" "))
+}
+
+impl Stmt {
+    fn number(&mut self, next: &mut usize) {
+        self.id = NodeId(*next);
+        *next += 1;
+        match &mut self.kind {
+            StmtKind::Let { value, .. } => value.iter_mut().for_each(|value| value.number(next)),
+            StmtKind::Assign { value, .. } => value.number(next),
+            StmtKind::Print { args, .. } => args.iter_mut().for_each(|arg| arg.number(next)),
+            StmtKind::Call { args, .. } => args.iter_mut().for_each(|arg| arg.value.number(next)),
+            StmtKind::If { cond, then, otherwise } => {
+                cond.number(next);
+                number_nodes(then, next);
+                if let Some(otherwise) = otherwise {
+                    number_nodes(otherwise, next);
+                }
+            }
+            StmtKind::For { start, end, step, body, .. } => {
+                start.number(next);
+                end.number(next);
+                step.iter_mut().for_each(|step| step.number(next));
+                number_nodes(body, next);
+            }
+            StmtKind::While { cond, body } => {
+                cond.number(next);
+                number_nodes(body, next);
+            }
+            StmtKind::Break(value) => value.iter_mut().for_each(|value| value.number(next)),
+            StmtKind::Next => {}
+            StmtKind::Fn(function) => number_nodes(&mut Rc::make_mut(function).body, next),
+        }
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        match &self.kind {
+            StmtKind::Let { name, ty, value, local } => {
+                let keyword = if *local { "local" } else { "let" };
+                match (ty, value) {
+                    (Some(ty), Some(value)) => format!("({} ({} {}) {})", keyword, name, ty, value.to_sexpr()),
+                    (Some(ty), None) => format!("({} ({} {}))", keyword, name, ty),
+                    (None, Some(value)) => format!("({} {} {})", keyword, name, value.to_sexpr()),
+                    (None, None) => format!("({} {})", keyword, name),
+                }
+            }
+            StmtKind::Assign { name, value } => format!("(= {} {})", name, value.to_sexpr()),
+            StmtKind::Print {
+                args,
+                newline,
+                precision,
+                to_stderr,
+            } => {
+                let keyword = match (to_stderr, newline) {
+                    (false, false) => "print",
+                    (false, true) => "println",
+                    (true, false) => "eprint",
+                    (true, true) => "eprintln",
+                };
+                let mut s = keyword.to_string();
+                for arg in args {
+                    s += &format!(" {}", arg.to_sexpr());
+                }
+                match precision {
+                    Some(p) => format!("({} (precision {}))", s, p),
+                    None => format!("({})", s),
+                }
+            }
+            StmtKind::Call { name, args } => {
+                let mut s = format!("(call {}", name);
+                for arg in args {
+                    s.push(' ');
+                    s.push_str(&arg.to_sexpr());
+                }
+                s.push(')');
+                s
+            }
+            StmtKind::If { cond, then, otherwise } => match otherwise {
+                Some(otherwise) => format!("(if {} {} {})", cond.to_sexpr(), block_sexpr(then), block_sexpr(otherwise)),
+                None => format!("(if {} {})", cond.to_sexpr(), block_sexpr(then)),
+            },
+            StmtKind::For { var, start, end, step, body } => match step {
+                Some(step) => format!(
+                    "(for {} {} {} {} {})",
+                    var,
+                    start.to_sexpr(),
+                    end.to_sexpr(),
+                    step.to_sexpr(),
+                    block_sexpr(body)
+                ),
+                None => format!("(for {} {} {} {})", var, start.to_sexpr(), end.to_sexpr(), block_sexpr(body)),
+            },
+            StmtKind::While { cond, body } => format!("(while {} {})", cond.to_sexpr(), block_sexpr(body)),
+            StmtKind::Break(Some(value)) => format!("(break {})", value.to_sexpr()),
+            StmtKind::Break(None) => "(break)".to_string(),
+            StmtKind::Next => "(next)".to_string(),
+            StmtKind::Fn(function) => function.to_sexpr(),
+        }
+    }
+}
+
+impl Expr {
+    fn number(&mut self, next: &mut usize) {
+        self.id = NodeId(*next);
+        *next += 1;
+        match &mut self.kind {
+            ExprKind::Integer(_) | ExprKind::Float(_) | ExprKind::Str(_) | ExprKind::Bool(_) | ExprKind::Var(_) => {}
+            ExprKind::Unary { expr, .. }
+            | ExprKind::ToStr(expr)
+            | ExprKind::ToInt(expr)
+            | ExprKind::ToFloat(expr)
+            | ExprKind::Len(expr)
+            | ExprKind::Cast { expr, .. }
+            | ExprKind::Ascription { expr, .. } => expr.number(next),
+            ExprKind::Binary { lhs, rhs, .. }
+            | ExprKind::Index {
+                array: lhs,
+                index: rhs,
+            } => {
+                lhs.number(next);
+                rhs.number(next);
+            }
+            ExprKind::Array(elems) | ExprKind::Builtin { args: elems, .. } => elems.iter_mut().for_each(|elem| elem.number(next)),
+        }
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        match &self.kind {
+            ExprKind::Integer(n) => n.to_string(),
+            ExprKind::Float(x) => format!("{:?}", x), // keeps the decimal point of 1.0
+            ExprKind::Str(s) => format!("{:?}", s),
+            ExprKind::Bool(b) => b.to_string(),
+            ExprKind::Var(name) => name.clone(),
+            ExprKind::Unary { op, expr } => format!("({} {})", op.symbol(), expr.to_sexpr()),
+            ExprKind::Binary { op, lhs, rhs } => format!("({} {} {})", op.symbol(), lhs.to_sexpr(), rhs.to_sexpr()),
+            ExprKind::ToStr(expr) => format!("(to_str {})", expr.to_sexpr()),
+            ExprKind::ToInt(expr) => format!("(to_int {})", expr.to_sexpr()),
+            ExprKind::ToFloat(expr) => format!("(to_float {})", expr.to_sexpr()),
+            ExprKind::Array(elems) => {
+                let mut s = "(array".to_string();
+                for elem in elems {
+                    s.push(' ');
+                    s.push_str(&elem.to_sexpr());
+                }
+                s.push(')');
+                s
+            }
+            ExprKind::Index { array, index } => format!("(index {} {})", array.to_sexpr(), index.to_sexpr()),
+            ExprKind::Len(expr) => format!("(len {})", expr.to_sexpr()),
+            ExprKind::Builtin { name, args } => {
+                let mut s = format!("({}", name.name());
+                for arg in args {
+                    s += &format!(" {}", arg.to_sexpr());
+                }
+                s + ")"
+            }
+            ExprKind::Cast { expr, ty } => format!("(as {} {})", expr.to_sexpr(), ty),
+            ExprKind::Ascription { expr, ty } => format!("(: {} {})", expr.to_sexpr(), ty),
+        }
+    }
+}
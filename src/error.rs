@@ -0,0 +1,76 @@
+use std::{error, fmt};
+
+use crate::eval::RuntimeError;
+use crate::lexer::LexError;
+use crate::parser::ParseError;
+use crate::resolve::ResolveError;
+use crate::typeck::TypeError;
+
+// Error of any phase, for the programs embedding the language
+#[derive(Debug)]
+pub enum MplError {
+    Lex(LexError),
+    Parse(ParseError),
+    Resolve(ResolveError),
+    Type(TypeError),
+    Runtime(RuntimeError),
+}
+
+impl From<LexError> for MplError {
+    fn from(e: LexError) -> Self {
+        Self::Lex(e)
+    }
+}
+
+// the lexing errors met by the parser are reported as lexing errors
+impl From<ParseError> for MplError {
+    fn from(e: ParseError) -> Self {
+        match e {
+            ParseError::Lex(e) => Self::Lex(e),
+            e => Self::Parse(e),
+        }
+    }
+}
+
+impl From<ResolveError> for MplError {
+    fn from(e: ResolveError) -> Self {
+        Self::Resolve(e)
+    }
+}
+
+impl From<TypeError> for MplError {
+    fn from(e: TypeError) -> Self {
+        Self::Type(e)
+    }
+}
+
+impl From<RuntimeError> for MplError {
+    fn from(e: RuntimeError) -> Self {
+        Self::Runtime(e)
+    }
+}
+
+// Displayed as the error of the phase
+impl fmt::Display for MplError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lex(e) => write!(f, "{}", e),
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::Resolve(e) => write!(f, "{}", e),
+            Self::Type(e) => write!(f, "{}", e),
+            Self::Runtime(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for MplError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Lex(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Resolve(e) => Some(e),
+            Self::Type(e) => Some(e),
+            Self::Runtime(e) => Some(e),
+        }
+    }
+}
@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::{error, fmt};
+
+use crate::ast::{BinaryOp, Block, Builtin, Expr, ExprKind, Function, Program, Stmt, StmtKind, Type, UnaryOp};
+use crate::lexer::Position;
+
+// Runtime value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i32),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+// Format how a value is printed, an integral float keeps its decimal point
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(x) if x.fract() == 0.0 && x.is_finite() => write!(f, "{:.1}", x),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Array(elems) => {
+                write!(f, "[")?;
+                for (k, elem) in elems.iter().enumerate() {
+                    if k > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub message: String,
+    pub pos: Position,
+}
+
+// Format how a runtime error is displayed
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Runtime error : [{}] at {} ({}:{})",
+            self.message, self.pos.file_name, self.pos.line, self.pos.col
+        )
+    }
+}
+
+impl error::Error for RuntimeError {}
+
+// How a statement ends
+enum Flow {
+    Normal,
+    Break,
+    Next,
+}
+
+// Tree walking interpreter, the program must have been type checked
+pub struct Interpreter<'a> {
+    functions: HashMap<&'a str, &'a Function>,
+    local_functions: Vec<HashMap<String, Rc<Function>>>, // functions declared in the enclosing blocks
+    scopes: Vec<HashMap<String, Value>>, // variables of the current function call
+    out: Box<dyn Write + 'a>,
+    err: Box<dyn Write + 'a>,
+}
+
+impl<'a> Default for Interpreter<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Interpreter<'a> {
+    // interpreter printing to stdout and stderr
+    pub fn new() -> Self {
+        Self::with_output(Box::new(io::stdout()), Box::new(io::stderr()))
+    }
+
+    pub fn with_output(out: Box<dyn Write + 'a>, err: Box<dyn Write + 'a>) -> Self {
+        Self {
+            functions: HashMap::new(),
+            local_functions: Vec::new(),
+            scopes: Vec::new(),
+            out,
+            err,
+        }
+    }
+
+    // run the main function
+    pub fn run(&mut self, program: &'a Program) -> Result<(), RuntimeError> {
+        self.run_entry(program, "main")
+    }
+
+    // run the given entry function, it can't take parameters
+    pub fn run_entry(&mut self, program: &'a Program, entry: &str) -> Result<(), RuntimeError> {
+        self.functions = program.functions().map(|f| (f.name.as_str(), f)).collect();
+        let main = program.function(entry).ok_or_else(|| RuntimeError {
+            message: format!("{} function not found", entry),
+            pos: Position::default(),
+        })?;
+        if !main.params.is_empty() {
+            return Err(Self::error(format!("entry function {} can't take parameters", entry), &main.pos));
+        }
+        self.call(main, Vec::new(), Vec::new())?;
+        self.out.flush().and_then(|_| self.err.flush()).map_err(|e| Self::error(e.to_string(), &main.pos))
+    }
+
+    // run the statements of a REPL line, the variables and functions they declare stay
+    // for the next lines
+    pub fn exec_statements(&mut self, block: &Block) -> Result<(), RuntimeError> {
+        if self.scopes.is_empty() {
+            self.scopes.push(HashMap::new());
+            self.local_functions.push(HashMap::new());
+        }
+        let result = block.iter().try_for_each(|stmt| self.exec_stmt(stmt).map(|_| ()));
+        self.out.flush().and_then(|_| self.err.flush()).map_err(|e| RuntimeError {
+            message: e.to_string(),
+            pos: Position::default(),
+        })?;
+        result
+    }
+
+    fn error(message: String, pos: &Position) -> RuntimeError {
+        RuntimeError {
+            message,
+            pos: pos.clone(),
+        }
+    }
+
+    // run a function body in a new frame, calling an empty function does nothing. The body sees
+    // the functions of the blocks around its declaration, not the ones around the call
+    fn call(
+        &mut self,
+        function: &Function,
+        args: Vec<Value>,
+        local_functions: Vec<HashMap<String, Rc<Function>>>,
+    ) -> Result<(), RuntimeError> {
+        let params = function
+            .params
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| (param.name.clone(), Self::promote(arg, &param.ty)))
+            .collect();
+        let caller = std::mem::replace(&mut self.scopes, vec![params]);
+        let caller_functions = std::mem::replace(&mut self.local_functions, local_functions);
+        let result = self.exec_block(&function.body);
+        self.scopes = caller;
+        self.local_functions = caller_functions;
+        result.map(|_| ())
+    }
+
+    // an int stored where a float is expected becomes a float
+    fn promote(value: Value, ty: &Type) -> Value {
+        match (value, ty) {
+            (Value::Int(n), Type::Float) => Value::Float(n as f64),
+            (value, _) => value,
+        }
+    }
+
+    // value of a variable declared without one, it keeps the type for the promotions
+    fn zero(ty: &Type) -> Value {
+        match ty {
+            Type::Int => Value::Int(0),
+            Type::Float => Value::Float(0.0),
+            Type::Str => Value::Str(String::new()),
+            Type::Bool => Value::Bool(false),
+            Type::Array(_) => Value::Array(Vec::new()),
+        }
+    }
+
+    fn declare(&mut self, name: &str, value: Value) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+    }
+
+    fn lookup(&self, name: &str, pos: &Position) -> Result<&Value, RuntimeError> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .ok_or_else(|| Self::error(format!("unknown variable {}", name), pos))
+    }
+
+    fn assign(&mut self, name: &str, value: Value, pos: &Position) -> Result<(), RuntimeError> {
+        let slot = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+            .ok_or_else(|| Self::error(format!("unknown variable {}", name), pos))?;
+        *slot = match slot {
+            Value::Float(_) => Self::promote(value, &Type::Float),
+            _ => value,
+        };
+        Ok(())
+    }
+
+    fn assign_local(&mut self, name: &str, value: Value) {
+        if let Some(slot) = self.scopes[0].get_mut(name) {
+            *slot = match slot {
+                Value::Float(_) => Self::promote(value, &Type::Float),
+                _ => value,
+            };
+        }
+    }
+
+    fn exec_block(&mut self, block: &Block) -> Result<Flow, RuntimeError> {
+        self.scopes.push(HashMap::new());
+        self.local_functions.push(HashMap::new());
+        let mut flow = Ok(Flow::Normal);
+        for stmt in block {
+            flow = self.exec_stmt(stmt);
+            if !matches!(flow, Ok(Flow::Normal)) {
+                break;
+            }
+        }
+        self.local_functions.pop();
+        self.scopes.pop();
+        flow
+    }
+
+    fn exec_stmt(&mut self, stmt: &Stmt) -> Result<Flow, RuntimeError> {
+        match &stmt.kind {
+            StmtKind::Let { name, ty, value, local } => {
+                // declaring a local of the function again assigns it
+                if *local && self.scopes[0].contains_key(name) {
+                    if let Some(value) = value {
+                        let value = self.eval(value)?;
+                        self.assign_local(name, value);
+                    }
+                    return Ok(Flow::Normal);
+                }
+                let value = match (ty, value) {
+                    (Some(ty), Some(value)) => Self::promote(self.eval(value)?, ty),
+                    (None, Some(value)) => self.eval(value)?,
+                    // the resolver checked that the variable is assigned before use
+                    (Some(ty), None) => Self::zero(ty),
+                    (None, None) => return Err(Self::error(format!("variable {} has no type", name), &stmt.pos)),
+                };
+                if *local {
+                    self.scopes[0].insert(name.clone(), value);
+                } else {
+                    self.declare(name, value);
+                }
+            }
+            StmtKind::Assign { name, value } => {
+                let value = self.eval(value)?;
+                self.assign(name, value, &stmt.pos)?;
+            }
+            StmtKind::Print {
+                args,
+                newline,
+                precision,
+                to_stderr,
+            } => {
+                // every value is evaluated before anything is printed
+                let mut texts = Vec::new();
+                for arg in args {
+                    let value = self.eval(arg)?;
+                    texts.push(match (precision, &value) {
+                        (Some(p), Value::Float(x)) => format!("{:.*}", p, x),
+                        _ => value.to_string(),
+                    });
+                }
+                let text = texts.join(" ");
+                let end = if *newline { "\n" } else { "" };
+                let out = if *to_stderr { &mut self.err } else { &mut self.out };
+                write!(out, "{}{}", text, end).map_err(|e| Self::error(e.to_string(), &stmt.pos))?;
+            }
+            StmtKind::Call { name, args } => {
+                // a local function keeps the blocks up to its declaration, a top level one none
+                let (local, visible) = match self.local_functions.iter().rposition(|scope| scope.contains_key(name.as_str())) {
+                    Some(depth) => (Some(self.local_functions[depth][name.as_str()].clone()), self.local_functions[..=depth].to_vec()),
+                    None => (None, Vec::new()),
+                };
+                let function: &Function = match &local {
+                    Some(function) => function,
+                    None => self
+                        .functions
+                        .get(name.as_str())
+                        .copied()
+                        .ok_or_else(|| Self::error(format!("unknown function {}", name), &stmt.pos))?,
+                };
+                // the arguments are evaluated in source order, then put in the parameter order
+                let order = function.arg_order(args).map_err(|message| Self::error(message, &stmt.pos))?;
+                let mut values: Vec<(usize, Value)> = Vec::new();
+                for (arg, index) in args.iter().zip(order) {
+                    values.push((index, self.eval(&arg.value)?));
+                }
+                values.sort_by_key(|(index, _)| *index);
+                self.call(function, values.into_iter().map(|(_, value)| value).collect(), visible)?;
+            }
+            StmtKind::If { cond, then, otherwise } => {
+                if self.eval(cond)? == Value::Bool(true) {
+                    return self.exec_block(then);
+                } else if let Some(otherwise) = otherwise {
+                    return self.exec_block(otherwise);
+                }
+            }
+            StmtKind::For { var, start, end, step, body } => {
+                let start = self.eval(start)?;
+                let end = self.eval(end)?;
+                // without a step the loop counts towards the end, down when it is below the start
+                let step = match step {
+                    Some(step) => self.eval(step)?,
+                    None if Self::binary(BinaryOp::Gt, start.clone(), end.clone(), &stmt.pos)? == Value::Bool(true) => Value::Int(-1),
+                    None => Value::Int(1),
+                };
+                self.exec_for(var, start, end, step, body, &stmt.pos)?;
+            }
+            StmtKind::While { cond, body } => {
+                while self.eval(cond)? == Value::Bool(true) {
+                    if let Flow::Break = self.exec_block(body)? {
+                        break;
+                    }
+                }
+            }
+            StmtKind::Break(value) => {
+                if let Some(value) = value {
+                    self.eval(value)?;
+                }
+                return Ok(Flow::Break);
+            }
+            StmtKind::Next => return Ok(Flow::Next),
+            StmtKind::Fn(function) => {
+                if let Some(scope) = self.local_functions.last_mut() {
+                    scope.insert(function.name.clone(), function.clone());
+                }
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    // for loop, the counter is an int unless one of the bounds is a float
+    fn exec_for(&mut self, var: &str, start: Value, end: Value, step: Value, body: &Block, pos: &Position) -> Result<(), RuntimeError> {
+        let mut counter = start;
+        if [&counter, &end, &step].iter().any(|v| matches!(v, Value::Float(_))) {
+            counter = Self::promote(counter, &Type::Float);
+        }
+        let zero = Value::Int(0);
+        if step == zero || step == Value::Float(0.0) {
+            return Err(Self::error("for step can't be zero".to_string(), pos));
+        }
+        let ascending = Self::binary(BinaryOp::Gt, step.clone(), zero, pos)? == Value::Bool(true);
+        let cmp = if ascending { BinaryOp::Le } else { BinaryOp::Ge };
+        while Self::binary(cmp, counter.clone(), end.clone(), pos)? == Value::Bool(true) {
+            self.scopes.push(HashMap::from([(var.to_string(), counter.clone())]));
+            let flow = self.exec_block(body);
+            self.scopes.pop();
+            if let Flow::Break = flow? {
+                break;
+            }
+            // a counter going past the int range has gone past the end too
+            counter = match (&counter, &step) {
+                (Value::Int(n), Value::Int(s)) => match n.checked_add(*s) {
+                    Some(n) => Value::Int(n),
+                    None => break,
+                },
+                _ => Self::binary(BinaryOp::Add, counter, step.clone(), pos)?,
+            };
+        }
+        Ok(())
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        match &expr.kind {
+            ExprKind::Integer(n) => Ok(Value::Int(*n)),
+            ExprKind::Float(x) => Ok(Value::Float(*x)),
+            ExprKind::Str(s) => Ok(Value::Str(s.clone())),
+            ExprKind::Bool(b) => Ok(Value::Bool(*b)),
+            ExprKind::Var(name) => self.lookup(name, &expr.pos).cloned(),
+            ExprKind::Unary { op: UnaryOp::Neg, expr: operand } => match self.eval(operand)? {
+                Value::Int(n) => n.checked_neg().map(Value::Int).ok_or_else(|| Self::error("arithmetic overflow".to_string(), &expr.pos)),
+                Value::Float(x) => Ok(Value::Float(-x)),
+                value => Err(Self::error(format!("can't negate {}", value), &expr.pos)),
+            },
+            ExprKind::Binary { op, lhs, rhs } => {
+                let lhs = self.eval(lhs)?;
+                // && and || don't evaluate their right operand once the left one decides
+                match (op, &lhs) {
+                    (BinaryOp::And, Value::Bool(false)) | (BinaryOp::Or, Value::Bool(true)) => return Ok(lhs),
+                    _ => {}
+                }
+                let rhs = self.eval(rhs)?;
+                Self::binary(*op, lhs, rhs, &expr.pos)
+            }
+            ExprKind::ToStr(operand) => Ok(Value::Str(self.eval(operand)?.to_string())),
+            ExprKind::ToInt(operand) | ExprKind::ToFloat(operand) => {
+                let text = self.eval(operand)?.to_string();
+                let (value, ty) = match expr.kind {
+                    ExprKind::ToInt(_) => (text.trim().parse::<i32>().ok().map(Value::Int), "int"),
+                    _ => (text.trim().parse::<f64>().ok().map(Value::Float), "float"),
+                };
+                value.ok_or_else(|| Self::error(format!("cannot parse '{}' as {}", text, ty), &expr.pos))
+            }
+            ExprKind::Array(elems) => {
+                let mut values = elems.iter().map(|elem| self.eval(elem)).collect::<Result<Vec<_>, _>>()?;
+                // mixed int and float elements are all floats
+                if values.iter().any(|v| matches!(v, Value::Float(_))) {
+                    values = values.into_iter().map(|v| Self::promote(v, &Type::Float)).collect();
+                }
+                Ok(Value::Array(values))
+            }
+            ExprKind::Index { array, index } => {
+                let array = self.eval(array)?;
+                let index = self.eval(index)?;
+                match (array, index) {
+                    (Value::Array(mut elems), Value::Int(i)) => {
+                        let len = elems.len();
+                        if i < 0 || i as usize >= len {
+                            return Err(Self::error(format!("index out of bounds, {} for a length of {}", i, len), &expr.pos));
+                        }
+                        Ok(elems.swap_remove(i as usize))
+                    }
+                    (array, index) => Err(Self::error(format!("can't index {} with {}", array, index), &expr.pos)),
+                }
+            }
+            ExprKind::Len(operand) => match self.eval(operand)? {
+                Value::Array(elems) => Ok(Value::Int(elems.len() as i32)),
+                Value::Str(s) => Ok(Value::Int(s.chars().count() as i32)),
+                value => Err(Self::error(format!("can't take the length of {}", value), &expr.pos)),
+            },
+            ExprKind::Builtin { name, args } => {
+                let mut values = args.iter().map(|arg| self.eval(arg)).collect::<Result<Vec<_>, _>>()?;
+                if values.iter().any(|v| matches!(v, Value::Float(_))) {
+                    values = values.into_iter().map(|v| Self::promote(v, &Type::Float)).collect();
+                }
+                match (name, values.as_slice()) {
+                    (Builtin::Abs, [Value::Int(n)]) => {
+                        n.checked_abs().map(Value::Int).ok_or_else(|| Self::error("arithmetic overflow".to_string(), &expr.pos))
+                    }
+                    (Builtin::Abs, [Value::Float(x)]) => Ok(Value::Float(x.abs())),
+                    (Builtin::Min | Builtin::Max, [a, b]) => {
+                        let less = Self::binary(BinaryOp::Lt, a.clone(), b.clone(), &expr.pos)? == Value::Bool(true);
+                        Ok(if less == (*name == Builtin::Min) { a.clone() } else { b.clone() })
+                    }
+                    _ => Err(Self::error(format!("{} can't take {} argument(s)", name.name(), values.len()), &expr.pos)),
+                }
+            }
+            ExprKind::Cast { expr: operand, ty } => match (self.eval(operand)?, ty) {
+                (Value::Int(n), Type::Float) => Ok(Value::Float(n as f64)),
+                // a float out of the int range, infinite or NaN, has no int to truncate to
+                (Value::Float(x), Type::Int) => {
+                    let x = x.trunc();
+                    if x.is_finite() && (i32::MIN as f64..=i32::MAX as f64).contains(&x) {
+                        Ok(Value::Int(x as i32))
+                    } else {
+                        Err(Self::error("arithmetic overflow".to_string(), &expr.pos))
+                    }
+                }
+                (value, _) => Ok(value),
+            },
+            ExprKind::Ascription { expr: operand, ty } => Ok(Self::promote(self.eval(operand)?, ty)),
+        }
+    }
+
+    pub(crate) fn binary(op: BinaryOp, lhs: Value, rhs: Value, pos: &Position) -> Result<Value, RuntimeError> {
+        match (lhs, rhs) {
+            (Value::Int(a), Value::Int(b)) => {
+                let result = match op {
+                    BinaryOp::Add => a.checked_add(b),
+                    BinaryOp::Sub => a.checked_sub(b),
+                    BinaryOp::Mul => a.checked_mul(b),
+                    BinaryOp::Div if b == 0 => return Err(Self::error("division by zero".to_string(), pos)),
+                    BinaryOp::Div => a.checked_div(b),
+                    _ => return Ok(Value::Bool(Self::compare(op, a.cmp(&b)))),
+                };
+                result.map(Value::Int).ok_or_else(|| Self::error("arithmetic overflow".to_string(), pos))
+            }
+            (Value::Int(a), Value::Float(b)) => Self::binary(op, Value::Float(a as f64), Value::Float(b), pos),
+            (Value::Float(a), Value::Int(b)) => Self::binary(op, Value::Float(a), Value::Float(b as f64), pos),
+            (Value::Float(a), Value::Float(b)) => match op {
+                BinaryOp::Add => Ok(Value::Float(a + b)),
+                BinaryOp::Sub => Ok(Value::Float(a - b)),
+                BinaryOp::Mul => Ok(Value::Float(a * b)),
+                BinaryOp::Div => Ok(Value::Float(a / b)),
+                _ => Ok(Value::Bool(a.partial_cmp(&b).is_some_and(|o| Self::compare(op, o)))),
+            },
+            (Value::Str(a), Value::Str(b)) if op == BinaryOp::Add => Ok(Value::Str(a + &b)),
+            (Value::Bool(a), Value::Bool(b)) if op == BinaryOp::And => Ok(Value::Bool(a && b)),
+            (Value::Bool(a), Value::Bool(b)) if op == BinaryOp::Or => Ok(Value::Bool(a || b)),
+            (a, b) => match op {
+                BinaryOp::Eq => Ok(Value::Bool(a == b)),
+                BinaryOp::NotEq => Ok(Value::Bool(a != b)),
+                _ => Err(Self::error(format!("invalid operands {} and {}", a, b), pos)),
+            },
+        }
+    }
+
+    // result of a comparison operator for an ordering
+    fn compare(op: BinaryOp, ordering: std::cmp::Ordering) -> bool {
+        match op {
+            BinaryOp::Eq => ordering.is_eq(),
+            BinaryOp::NotEq => ordering.is_ne(),
+            BinaryOp::Lt => ordering.is_lt(),
+            BinaryOp::Le => ordering.is_le(),
+            BinaryOp::Gt => ordering.is_gt(),
+            _ => ordering.is_ge(),
+        }
+    }
+}
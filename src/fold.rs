@@ -0,0 +1,98 @@
+use std::rc::Rc;
+
+use crate::ast::{Block, Expr, ExprKind, Program, StmtKind, UnaryOp};
+use crate::eval::{Interpreter, Value};
+
+// Constant folding, run once the program is checked. An operation on literals is replaced by
+// its value, computed as the interpreter does. An operation that fails at runtime, like an
+// overflow or a division by zero, is kept for the interpreter to report at its position
+pub fn fold_program(program: &mut Program) {
+    for function in &mut program.functions {
+        fold_block(&mut function.body);
+    }
+}
+
+pub fn fold_block(block: &mut Block) {
+    for stmt in block {
+        match &mut stmt.kind {
+            StmtKind::Let { value, .. } | StmtKind::Break(value) => value.iter_mut().for_each(fold_expr),
+            StmtKind::Assign { value, .. } => fold_expr(value),
+            StmtKind::Print { args, .. } => args.iter_mut().for_each(fold_expr),
+            StmtKind::Call { args, .. } => args.iter_mut().for_each(|arg| fold_expr(&mut arg.value)),
+            StmtKind::If { cond, then, otherwise } => {
+                fold_expr(cond);
+                fold_block(then);
+                otherwise.iter_mut().for_each(fold_block);
+            }
+            StmtKind::For { start, end, step, body, .. } => {
+                fold_expr(start);
+                fold_expr(end);
+                step.iter_mut().for_each(fold_expr);
+                fold_block(body);
+            }
+            StmtKind::While { cond, body } => {
+                fold_expr(cond);
+                fold_block(body);
+            }
+            StmtKind::Next => {}
+            StmtKind::Fn(function) => fold_block(&mut Rc::make_mut(function).body),
+        }
+    }
+}
+
+// value of a literal
+fn literal(expr: &Expr) -> Option<Value> {
+    match &expr.kind {
+        ExprKind::Integer(n) => Some(Value::Int(*n)),
+        ExprKind::Float(x) => Some(Value::Float(*x)),
+        ExprKind::Str(s) => Some(Value::Str(s.clone())),
+        ExprKind::Bool(b) => Some(Value::Bool(*b)),
+        _ => None,
+    }
+}
+
+// the operands are folded first, so 1 + 2 * 3 is folded from the product up
+fn fold_expr(expr: &mut Expr) {
+    match &mut expr.kind {
+        ExprKind::Integer(_) | ExprKind::Float(_) | ExprKind::Str(_) | ExprKind::Bool(_) | ExprKind::Var(_) => {}
+        ExprKind::Unary { expr, .. }
+        | ExprKind::ToStr(expr)
+        | ExprKind::ToInt(expr)
+        | ExprKind::ToFloat(expr)
+        | ExprKind::Len(expr)
+        | ExprKind::Cast { expr, .. }
+        | ExprKind::Ascription { expr, .. } => fold_expr(expr),
+        ExprKind::Binary { lhs, rhs, .. }
+        | ExprKind::Index {
+            array: lhs,
+            index: rhs,
+        } => {
+            fold_expr(lhs);
+            fold_expr(rhs);
+        }
+        ExprKind::Array(elems) | ExprKind::Builtin { args: elems, .. } => elems.iter_mut().for_each(fold_expr),
+    }
+    let value = match &expr.kind {
+        ExprKind::Unary { op: UnaryOp::Neg, expr: operand } => match literal(operand) {
+            Some(Value::Int(n)) => n.checked_neg().map(Value::Int),
+            Some(Value::Float(x)) => Some(Value::Float(-x)),
+            _ => None,
+        },
+        ExprKind::Binary { op, lhs, rhs } => match (literal(lhs), literal(rhs)) {
+            (Some(lhs), Some(rhs)) => Interpreter::binary(*op, lhs, rhs, &expr.pos).ok(),
+            _ => None,
+        },
+        ExprKind::Len(operand) => match literal(operand) {
+            Some(Value::Str(s)) => i32::try_from(s.chars().count()).ok().map(Value::Int),
+            _ => None,
+        },
+        _ => None,
+    };
+    expr.kind = match value {
+        Some(Value::Int(n)) => ExprKind::Integer(n),
+        Some(Value::Float(x)) => ExprKind::Float(x),
+        Some(Value::Str(s)) => ExprKind::Str(s),
+        Some(Value::Bool(b)) => ExprKind::Bool(b),
+        Some(Value::Array(_)) | None => return,
+    };
+}
@@ -0,0 +1,60 @@
+use std::ops::Range;
+
+use crate::lexer::{LexError, LexToken, Lexer};
+use crate::token::Token;
+
+const RESET: &str = "\x1b[0m";
+
+// ANSI color of a token kind, the others keep the terminal color
+fn color(token: &Token) -> Option<&'static str> {
+    match token {
+        Token::Str(_) => Some("\x1b[32m"),                      // green
+        Token::Integer(_) | Token::Float(_) => Some("\x1b[36m"), // cyan
+        Token::Comment(_) => Some("\x1b[90m"),                   // gray
+        Token::True | Token::False => Some("\x1b[1m"),
+        t if t.is_keyword() => Some("\x1b[1m"), // bold
+        _ => None,
+    }
+}
+
+// char range of each token in the source, from its position up to the next token without the
+// whitespace in between. No token ends with a whitespace, the comments are trimmed
+pub fn spans(src: &str, tokens: &[LexToken]) -> Vec<Range<usize>> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut line_starts = vec![0];
+    line_starts.extend(chars.iter().enumerate().filter(|(_, c)| **c == '\n').map(|(i, _)| i + 1));
+    let starts: Vec<usize> = tokens
+        .iter()
+        .map(|t| (line_starts.get(t.pos.line - 1).copied().unwrap_or(chars.len()) + t.pos.col - 1).min(chars.len()))
+        .collect();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(k, &start)| {
+            let mut end = starts.get(k + 1).copied().unwrap_or(chars.len()).max(start);
+            while end > start && chars[end - 1].is_whitespace() {
+                end -= 1;
+            }
+            start..end
+        })
+        .collect()
+}
+
+// the source with each token colored by its kind, the imports are not followed
+pub fn highlight(src: &str, file_name: &str) -> Result<String, LexError> {
+    let tokens = Lexer::tokenize_source_with_comments(src, file_name)?.tokens;
+    let chars: Vec<char> = src.chars().collect();
+    let mut out = String::new();
+    let mut done = 0;
+    for (t, span) in tokens.iter().zip(spans(src, &tokens)) {
+        out.extend(&chars[done..span.start]);
+        let text: String = chars[span.clone()].iter().collect();
+        match color(&t.token) {
+            Some(color) if !text.is_empty() => out += &format!("{}{}{}", color, text, RESET),
+            _ => out += &text,
+        }
+        done = span.end;
+    }
+    out.extend(&chars[done..]);
+    Ok(out)
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Where the lexer reads the source files from
+pub trait ImportResolver {
+    fn read(&self, path: &str) -> Result<String, io::Error>;
+
+    // directories are imported through their index file
+    fn is_dir(&self, _path: &str) -> bool {
+        false
+    }
+
+    fn is_file(&self, path: &str) -> bool {
+        self.read(path).is_ok()
+    }
+}
+
+// Read the source files from the disk
+pub struct FsResolver;
+
+impl ImportResolver for FsResolver {
+    fn read(&self, path: &str) -> Result<String, io::Error> {
+        fs::read_to_string(path)
+    }
+
+    fn is_dir(&self, path: &str) -> bool {
+        Path::new(path).is_dir()
+    }
+
+    fn is_file(&self, path: &str) -> bool {
+        Path::new(path).is_file()
+    }
+}
+
+// Source files kept in memory, by path
+#[derive(Default)]
+pub struct MemoryResolver {
+    files: HashMap<String, String>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: impl Into<String>, src: impl Into<String>) {
+        self.files.insert(path.into(), src.into());
+    }
+}
+
+impl ImportResolver for MemoryResolver {
+    fn read(&self, path: &str) -> Result<String, io::Error> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.to_string()))
+    }
+}
@@ -1,9 +1,15 @@
-use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::fs;
-use std::path::{Path, MAIN_SEPARATOR};
+use std::io::ErrorKind;
+use std::ops::Range;
+use std::path::{Path, MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
+use std::rc::Rc;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
+use crate::import::{FsResolver, ImportResolver};
+use crate::lint::Warning;
+use crate::parser::Parser;
 use crate::token::Token;
 
 #[derive(Debug, Clone)]
@@ -14,7 +20,7 @@ pub struct LexToken {
 
 impl fmt::Display for LexToken {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}:{}:{} [{:?}]\n", self.pos.file_name, self.pos.line, self.pos.col, self.token)
+        writeln!(f, "{}:{}:{} [{:?}]", self.pos.file_name, self.pos.line, self.pos.col, self.token)
     }
 }
 
@@ -22,24 +28,58 @@ pub struct TokenStream {
     pub tokens: Vec<LexToken>,
 }
 
+impl TokenStream {
+    // same stream without the comments, the other tokens keep their positions
+    pub fn filter_trivia(&self) -> TokenStream {
+        TokenStream {
+            tokens: self.tokens.iter().filter(|t| !t.token.is_trivia()).cloned().collect(),
+        }
+    }
+
+    // parser over this stream, without reading the files again
+    pub fn into_parser(self) -> Parser {
+        Parser::from_stream(self)
+    }
+
+    // number of tokens of each kind, the most frequent first then by name
+    pub fn stats(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for token in &self.tokens {
+            *counts.entry(token.token.kind_name()).or_default() += 1;
+        }
+        let mut stats: Vec<(String, usize)> = counts.into_iter().collect();
+        stats.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stats
+    }
+}
+
 // Display all tokens in the token stream
 impl fmt::Display for TokenStream {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the numbers are right aligned on the widest one
+        let width = self.tokens.len().to_string().len();
         for (i, token) in self.tokens.iter().enumerate() {
-            write!(f, "{} -> {}", i+1, token)?;
+            write!(f, "{:>width$} -> {}", i+1, token)?;
         }
         Ok(())
     }
 }
 
 // Lexer error
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Position {
     pub file_name: String, // source file name
     pub line: usize,       // line number
     pub col: usize,        // column number
 }
 
+// Start of an unnamed source, for the errors not tied to a file
+impl Default for Position {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
 impl Position {
     pub fn new(file_name: String) -> Self {
         Self {
@@ -48,6 +88,22 @@ impl Position {
             col: 1,
         }
     }
+
+    // start of the given file
+    pub fn start(file: impl Into<String>) -> Self {
+        Self::new(file.into())
+    }
+
+    // move n chars to the right on the same line
+    pub fn bump_col(&mut self, n: usize) {
+        self.col += n;
+    }
+
+    // move to the start of the next line
+    pub fn newline(&mut self) {
+        self.line += 1;
+        self.col = 1;
+    }
 }
 
 #[derive(Debug)]
@@ -59,9 +115,9 @@ pub struct LexError {
 // Format how a lex error is displayed
 impl std::fmt::Display for LexError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
+        writeln!(
             f,
-            "Token error : [{}] at {} ({}:{})\n",
+            "Token error : [{}] at {} ({}:{})",
             self.message, self.pos.file_name, self.pos.line, self.pos.col
         )
     }
@@ -73,7 +129,43 @@ impl From<std::io::Error> for LexError {
     fn from(error: std::io::Error) -> Self {
         LexError {
             message: format!("IO error: {}", error),
-            pos: Position::new(String::new()),
+            pos: Position::default(),
+        }
+    }
+}
+
+// Operators made of two chars
+const OPERATORS: [&str; 6] = ["==", "!=", "<=", ">=", "&&", "||"];
+
+// import statement as (index of import or from, index of its last token, file name,
+// functions selected by from)
+type ImportStmt = (usize, usize, String, Option<Vec<(String, Position)>>);
+
+// Imported file, with the position of its import statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub file_name: String,
+    pub pos: Position,
+}
+
+// settings of a lexer, the parser lexes with them and the imported files are read with them too
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexerOptions {
+    pub verbosity: u8, // 1 logs the files read, 2 also logs the import resolution steps
+    pub keep_trivia: bool, // produce the comments as tokens
+    pub import_markers: bool, // surround the tokens of each imported file with marker tokens
+    pub resolve_imports: bool, // splice the imported files, or leave the import statements as tokens
+    pub warn_tabs: bool, // warn on each tab outside the strings
+}
+
+impl Default for LexerOptions {
+    fn default() -> Self {
+        Self {
+            verbosity: 0,
+            keep_trivia: false,
+            import_markers: false,
+            resolve_imports: true,
+            warn_tabs: false,
         }
     }
 }
@@ -83,19 +175,133 @@ pub struct Lexer {
     src_text: String,
     pos: Position,
     i: usize, // current index in the source file
+    imports: Vec<Import>, // files imported by the last tokenize
+    import_graph: HashMap<String, Vec<String>>, // files imported by each file of the last tokenize
+    resolver: Rc<dyn ImportResolver>, // where the source files are read from
+    timings: Vec<(&'static str, Duration)>, // duration of each phase of the last tokenize
+    options: LexerOptions,
+    lines: usize, // lines of the files read by the last tokenize
+    keywords: Rc<HashMap<String, Token>>, // words read as these tokens before the built-in keywords
+    in_string: bool, // the chars read are in a string
+    warnings: Vec<Warning>, // warnings of the files read by the last tokenize
 }
 
 impl Lexer {
     pub fn new(src_filename: String) -> Self {
+        Self::with_resolver(src_filename, Rc::new(FsResolver))
+    }
+
+    // lexer reading the main file and its imports through a custom resolver
+    pub fn with_resolver(src_filename: String, resolver: Rc<dyn ImportResolver>) -> Self {
         let filename = src_filename.clone();
         Self {
             src_filename,
             src_text: String::new(),
             pos: Position::new(filename),
             i: 0,
+            imports: Vec::new(),
+            import_graph: HashMap::new(),
+            resolver,
+            timings: Vec::new(),
+            options: LexerOptions::default(),
+            lines: 0,
+            keywords: Rc::new(HashMap::new()),
+            in_string: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    // replace all the settings of LexerOptions at once
+    pub fn set_options(&mut self, options: LexerOptions) {
+        self.options = options;
+    }
+
+    // settings of the lexer, changed by the setters below too
+    pub fn options(&self) -> &LexerOptions {
+        &self.options
+    }
+
+    // produce the comments as Comment tokens instead of skipping them
+    pub fn set_keep_trivia(&mut self, keep_trivia: bool) {
+        self.options.keep_trivia = keep_trivia;
+    }
+
+    // mark where the tokens of each imported file start and end, the markers are trivia
+    pub fn set_import_markers(&mut self, import_markers: bool) {
+        self.options.import_markers = import_markers;
+    }
+
+    // extra words for the tokens, looked up before the built-in keywords which stay valid,
+    // e.g. "imprimer" for Token::Print. The imported files are read with them too
+    pub fn set_keywords(&mut self, keywords: HashMap<String, Token>) {
+        self.keywords = Rc::new(keywords);
+    }
+
+    // warn on each tab outside the strings, the imported files are checked too
+    pub fn set_warn_tabs(&mut self, warn_tabs: bool) {
+        self.options.warn_tabs = warn_tabs;
+    }
+
+    // warnings of the last tokenize
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    // lex the named file alone, its import statements are left as tokens
+    pub fn set_resolve_imports(&mut self, resolve_imports: bool) {
+        self.options.resolve_imports = resolve_imports;
+    }
+
+    // log the lexing steps to stderr
+    pub fn set_verbosity(&mut self, verbosity: u8) {
+        self.options.verbosity = verbosity;
+    }
+
+    fn log(&self, level: u8, message: &str) {
+        if self.options.verbosity >= level {
+            eprintln!("{}", message);
         }
     }
 
+    // duration of the lexing and import resolution phases of the last tokenize
+    pub fn timings(&self) -> &[(&'static str, Duration)] {
+        &self.timings
+    }
+
+    // files imported by the main source file, in source order
+    pub fn imports(&self) -> &[Import] {
+        &self.imports
+    }
+
+    // files imported by each file read by the last tokenize, in source order. A file importing
+    // nothing maps to an empty list
+    pub fn import_graph(&self) -> HashMap<String, Vec<String>> {
+        self.import_graph.clone()
+    }
+
+    // start over on another source kept in memory, the settings are kept
+    pub fn reset(&mut self, filename: String, src: String) {
+        self.pos = Position::new(filename.clone());
+        self.src_filename = filename;
+        self.src_text = src;
+        self.i = 0;
+        self.imports.clear();
+        self.import_graph.clear();
+        self.timings.clear();
+        self.warnings.clear();
+    }
+
+    // text of the named file, read through the resolver with the errors of tokenize
+    pub fn source_text(&mut self) -> Result<&str, LexError> {
+        self.read_source(None)?;
+        Ok(&self.src_text)
+    }
+
+    // lex the source given to reset, the imports are left as tokens
+    pub fn tokenize_text(&mut self) -> Result<TokenStream, LexError> {
+        Ok(TokenStream { tokens: self.scan()? })
+    }
+
     //save the state of the lexer
     fn save_state(&self) -> (usize, usize, usize) {
         (self.i, self.pos.col, self.pos.line)
@@ -111,11 +317,18 @@ impl Lexer {
     // get the next char in the source file
     fn get_next_char(&mut self) -> char {
         let c = self.src_text.chars().nth(self.i).unwrap_or('\0');
-        self.pos.col += 1;
+        // a char read again after a restore_state is reported once
+        if c == '\t' && self.options.warn_tabs && !self.in_string && self.warnings.last().is_none_or(|w| w.pos != self.pos) {
+            self.warnings.push(Warning {
+                message: "tab character, indent with spaces".to_string(),
+                pos: self.pos.clone(),
+            });
+        }
         self.i += 1;
         if c == '\n' {
-            self.pos.line += 1;
-            self.pos.col = 1;
+            self.pos.newline();
+        } else {
+            self.pos.bump_col(1);
         }
         c
     }
@@ -127,25 +340,50 @@ impl Lexer {
         }
     }
 
-    // get the next word in the source file
-    fn get_next_word(&mut self) -> Option<String> {
+    // read chars while the predicate accepts them, given the word read so far. A whitespace or
+    // the end of the file ends the scan and is consumed, a rejected char is left for the next token
+    fn scan_while(&mut self, accept: impl Fn(&mut Self, &str, char) -> bool) -> String {
         let mut word = String::new();
-        let (mut i_tmp, mut col_tmp, mut line_tmp) = self.save_state();
         loop {
+            if self.at_null_byte() {
+                break;
+            }
+            let state = self.save_state();
             let c = self.get_next_char();
             if c == '\0' || c == ' ' || c == '\n' || c == '\r' || c == '\t' {
                 break;
             }
-            match self.identify_token(&c.to_string()) {
-                Some(_) => { self.restore_state( (i_tmp, col_tmp, line_tmp) ); break; },
-                None => {word.push(c); (i_tmp, col_tmp, line_tmp) = self.save_state();}
+            if !accept(self, &word, c) {
+                self.restore_state(state);
+                break;
             }
+            word.push(c);
         }
+        word
+    }
+
+    // get the next word in the source file
+    fn get_next_word(&mut self) -> Option<String> {
+        // a lone char starting a two chars operator ('!') is kept to be reported. A word is
+        // whole up to a symbol or a quote, println"x" is println then a string but printx
+        // is one identifier
+        let word = self.scan_while(|lexer, word, c| word.is_empty() || !(lexer.is_symbol_start(c) || c == '"' || c == '\''));
         if word.is_empty() { None } else { Some(word) }
     }
 
-    // try to identify a symbol (one char only)
+    // check if a char starts a symbol, one char symbol or two chars operator
+    fn is_symbol_start(&mut self, c: char) -> bool {
+        self.identify_token(&c.to_string()).is_some() || OPERATORS.iter().any(|op| op.starts_with(c))
+    }
+
+    // try to identify a symbol, two chars operator first, the position stays on the first char
     fn try_symbol(&mut self) -> Option<Token> {
+        if let Some(word) = self.look_ahead(2)
+            && OPERATORS.contains(&word.as_str())
+        {
+            self.bump(2);
+            return self.identify_token(&word);
+        }
         let (i_tmp, col_tmp, line_tmp) = self.save_state();
         let mut word = String::new();
         let c = self.get_next_char();
@@ -160,21 +398,15 @@ impl Lexer {
     }
 
     // identify the token
-    fn identify_token(&mut self, word: &String) -> Option<Token> {
-        match Token::from_str(&*word) {
-            Ok(token) => Some(token),
-            Err(..) => None,
-        }
+    fn identify_token(&mut self, word: &str) -> Option<Token> {
+        self.keywords.get(word).cloned().or_else(|| Token::from_str(word).ok())
     }
 
     // skip whitespace
     fn skip_whitespace(&mut self) {
-        let mut c = self.get_next_char();
-        while c == ' ' || c == '\n' || c == '\r' || c == '\t' {
-            c = self.get_next_char();
+        while matches!(self.src_text.chars().nth(self.i), Some(' ' | '\n' | '\r' | '\t')) {
+            self.get_next_char();
         }
-        self.pos.col -= 1;
-        self.i -= 1;
     }
 
     // look ahead nb chars
@@ -188,12 +420,13 @@ impl Lexer {
 
     // skip comment single line
     fn skip_comment_single_line(&mut self) {
-        if let Some(look_ahead) = self.look_ahead(2) {
-            if look_ahead == "//" {
-                let mut c = self.get_next_char();
-                while c != '\n' {
-                    c = self.get_next_char();
-                }
+        if let Some(look_ahead) = self.look_ahead(2)
+            && look_ahead == "//"
+        {
+            // a null byte ends the comment and is reported by the caller
+            let mut c = self.get_next_char();
+            while c != '\n' && c != '\0' && !self.at_null_byte() {
+                c = self.get_next_char();
             }
         }
     }
@@ -218,6 +451,8 @@ impl Lexer {
                                 self.skip_whitespace();
                                 close = true; // comment is closed
                                 break; // exit loop
+                            } else if self.at_null_byte() {
+                                return Err(Self::null_byte_error(self.pos.clone()));
                             } else {
                                 // no, it was not the end of the comment
                                 self.get_next_char(); // get next char
@@ -242,43 +477,132 @@ impl Lexer {
         }
     }
 
+    // skip whitespace and comments, in any order, up to the next token. The comments are
+    // pushed as tokens when the trivia is kept
+    fn skip_trivia(&mut self, tokens: &mut Vec<LexToken>) -> Result<(), LexError> {
+        loop {
+            let start = self.i;
+            self.skip_whitespace();
+            let (comment_start, pos) = (self.i, self.pos.clone());
+            self.skip_comment_single_line();
+            self.push_comment(comment_start, pos, tokens);
+            let (comment_start, pos) = (self.i, self.pos.clone());
+            self.skip_comment_multiple_line()?;
+            self.push_comment(comment_start, pos, tokens);
+            if self.i == start {
+                return Ok(());
+            }
+        }
+    }
+
+    fn push_comment(&self, start: usize, pos: Position, tokens: &mut Vec<LexToken>) {
+        if self.options.keep_trivia && self.i > start {
+            let text: String = self.src_text.chars().skip(start).take(self.i - start).collect();
+            tokens.push(LexToken { token: Token::Comment(text.trim_end().to_string()), pos });
+        }
+    }
+
     // check if a char is a digit or a dot
     #[inline]
     fn is_digit(ch: char) -> bool {
         if ch == '.' { true } else { ch.is_ascii_digit() }
     }
 
+    // check if a number is an hexadecimal float waiting for the sign of its exponent
+    #[inline]
+    fn is_hex_exponent(word: &str) -> bool {
+        Self::is_hex(word) && word.ends_with(['p', 'P'])
+    }
+
+    #[inline]
+    fn is_hex(word: &str) -> bool {
+        word.starts_with("0x") || word.starts_with("0X")
+    }
+
+    // check if a decimal number is in scientific notation : 1e3, 1.5E-2
+    #[inline]
+    fn is_scientific(word: &str) -> bool {
+        !Self::is_hex(word) && word.contains(['e', 'E'])
+    }
+
+    // check if a decimal number is waiting for the sign of its exponent
+    #[inline]
+    fn is_scientific_exponent(word: &str) -> bool {
+        !Self::is_hex(word) && word.ends_with(['e', 'E'])
+    }
+
+    // name what is wrong with a decimal float that doesn't parse
+    fn float_error(word: &str) -> String {
+        let (mantissa, exponent) = match word.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+            None => (word, None),
+        };
+        if mantissa.matches('.').count() > 1 {
+            return format!("multiple decimal points in number [{}]", word);
+        }
+        if let Some(exponent) = exponent {
+            let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                return format!("invalid exponent in number [{}]", word);
+            }
+        }
+        format!("invalid float number format [{}]", word)
+    }
+
+    // parse an hexadecimal float like 0x1.8p3 : hexadecimal mantissa, decimal exponent of 2
+    fn parse_hex_float(word: &str) -> Option<f64> {
+        let (mantissa, exponent) = word.get(2..)?.split_once(['p', 'P'])?;
+        let exponent = exponent.parse::<i32>().ok()?;
+        let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+        let mut value = 0f64;
+        for c in int_part.chars() {
+            value = value * 16.0 + c.to_digit(16)? as f64;
+        }
+        let mut scale = 1.0 / 16.0;
+        for c in frac_part.chars() {
+            value += c.to_digit(16)? as f64 * scale;
+            scale /= 16.0;
+        }
+        Some(value * 2f64.powi(exponent))
+    }
+
+    // remove the '_' grouping digits (1_000.500_25), a '_' must sit between two digits
+    fn strip_underscores(word: &str) -> Option<String> {
+        let chars: Vec<char> = word.chars().collect();
+        let hex = Self::is_hex(word);
+        let is_digit = |c: &char| if hex { c.is_ascii_hexdigit() } else { c.is_ascii_digit() };
+        for (k, c) in chars.iter().enumerate() {
+            if *c == '_' && !(k > 0 && is_digit(&chars[k - 1]) && chars.get(k + 1).is_some_and(is_digit)) {
+                return None;
+            }
+        }
+        Some(word.replace('_', ""))
+    }
+
     fn try_number(&mut self) -> Option<String> {
-        let mut word = String::new();
-        let (i_tmp, col_tmp, line_tmp) = self.save_state();
+        // a dot right after an identifier is a Dot symbol : foo.5 is foo . 5, not foo 0.5
+        let prev = self.i.checked_sub(1).and_then(|i| self.src_text.chars().nth(i)).unwrap_or('\0');
+        let state = self.save_state();
         let c = self.get_next_char();
-        let (mut i_tmp2, mut col_tmp2, mut line_tmp2) = self.save_state();
-        if Self::is_digit(c) {
-            if c == '.' {
-                let n = self.src_text.chars().nth(self.i).unwrap_or('\0');
-                println!(">>>{}",n);
-                if !n.is_ascii_digit() {
-                    println!("KO");
-                    self.restore_state((i_tmp, col_tmp, line_tmp));
-                    return None;
-                }
+        let next = self.src_text.chars().nth(self.i).unwrap_or('\0');
+        self.restore_state(state);
+        // a dot only starts a number when a digit follows
+        if !Self::is_digit(c) || (c == '.' && (!next.is_ascii_digit() || prev.is_alphanumeric() || prev == '_')) {
+            return None;
+        }
+        Some(self.scan_while(|lexer, word, c| {
+            if OPERATORS.iter().any(|op| op.starts_with(c)) {
+                return false;
             }
-            let mut c = c; // Use the first character we already read
-            while c != '\0' {
-                if c == ' ' || c == '\n' || c == '\r' || c == '\t' {
-                    break;
-                }
-                match self.identify_token(&c.to_string()) {
-                    None | Some(Token::Dot) => {word.push(c); (i_tmp2, col_tmp2, line_tmp2) = self.save_state();}
-                    Some(_) => { self.restore_state( (i_tmp2, col_tmp2, line_tmp2) ); break; },
-                }
-                c = self.get_next_char();
+            match lexer.identify_token(&c.to_string()) {
+                None | Some(Token::Dot) => true,
+                Some(Token::Plus | Token::Minus) => Self::is_hex_exponent(word) || Self::is_scientific_exponent(word),
+                Some(_) => false,
             }
-            Some(word)
-        } else {
-            self.restore_state((i_tmp, col_tmp, line_tmp));
-            None
-        }
+        }))
     }
 
     // try to identify a string
@@ -292,12 +616,24 @@ impl Lexer {
             return Ok(None);
         }
 
+        self.in_string = true;
+        let result = self.read_string(start);
+        self.in_string = false;
+        result.map(Some)
+    }
+
+    // read the rest of a string, up to the closing quote
+    fn read_string(&mut self, start: char) -> Result<String, LexError> {
         // English: Use the opening quote as the required closing delimiter.
         let quote = start;
         let mut out = String::new();
         let err_pos = self.pos.clone(); // English: position to report if unclosed
 
         loop {
+            if self.at_null_byte() {
+                return Err(Self::null_byte_error(self.pos.clone()));
+            }
+            let pos = self.pos.clone();
             let c = self.get_next_char();
             match c {
                 '\0' | '\n' | '\r' => {
@@ -306,12 +642,36 @@ impl Lexer {
                         pos: err_pos,
                     })
                 }
-                _ if c == quote => return Ok(Some(out)),
+                _ if c == quote => return Ok(out),
+                // \" and \' keep the quote in the string, \\ is a backslash. A backslash at the
+                // end of the line leaves the string unclosed
+                '\\' => match self.src_text.chars().nth(self.i) {
+                    Some('"' | '\'' | '\\') => out.push(self.get_next_char()),
+                    None | Some('\0' | '\n' | '\r') => {}
+                    Some(e) => {
+                        return Err(LexError {
+                            message: format!("unknown escape '\\{}' in string", e),
+                            pos,
+                        })
+                    }
+                },
                 _ => out.push(c),
             }
         }
     }
 
+    // a '\0' read before the end of the file is a real null byte, not the end marker
+    fn at_null_byte(&self) -> bool {
+        self.src_text.chars().nth(self.i) == Some('\0')
+    }
+
+    fn null_byte_error(pos: Position) -> LexError {
+        LexError {
+            message: "null byte in source".to_string(),
+            pos,
+        }
+    }
+
     // check if the end of the file is reached
     #[inline]
     fn eof(&self) -> bool {
@@ -320,11 +680,11 @@ impl Lexer {
 
     // check if the word is a valid identifier, must start with a letter
     #[inline]
-    fn is_ident_valid(&self, word: &String) -> bool {
+    fn is_ident_valid(&self, word: &str) -> bool {
         let mut valid = true;
         match word.chars().next() {
             Some(c) => {
-                if !c.is_ascii_alphabetic() {
+                if !c.is_ascii_alphabetic() && c != '_' {
                     valid = false;
                 }
             }
@@ -341,62 +701,269 @@ impl Lexer {
         valid
     }
 
-    fn get_import_list(tokens: &Vec<LexToken>) -> Result<Vec<(usize,String)>, LexError> {
-        let mut imports: Vec<(usize, String)> = Vec::new();
-        let mut k=0usize;
-        for (i,t) in tokens.windows(2).enumerate() {
-            let (cur, next) = (&t[0], &t[1]);
-            if cur.token == Token::Import {
-                if let Token::Str(ref s) = next.token {
-                    if !imports.iter().any(|(_, exist)| *exist == *s) {
-                        if k > 0 {
-                            let previous_import_index = imports[k - 1].0;
-                            let imports_are_not_consecutive = (previous_import_index + 2) != i;
-                            if imports_are_not_consecutive {
-                                return Err(LexError {
-                                    message: format!("import can't be after instruction"),
-                                    pos: next.pos.clone(),
-                                });
-                            }
-                        }
-                        k+=1;
-                        imports.push((i,s.clone()));
-                    } else {
+    // list the imports as (index of import or from, index of the last token of the statement,
+    // file name, functions selected by from). Imports must open the file, only the comments kept
+    // as tokens can come before them
+    fn get_import_list(tokens: &[LexToken]) -> Result<Vec<ImportStmt>, LexError> {
+        let mut imports: Vec<ImportStmt> = Vec::new();
+        // index of the tokens meaningful for the grammar, the last one is always eof
+        let significant: Vec<usize> = (0..tokens.len()).filter(|&k| !tokens[k].token.is_trivia()).collect();
+        let mut header = 0; // tokens of the import statements read so far
+        let mut p = 0;
+        while p < significant.len() {
+            let i = significant[p];
+            if tokens[i].token != Token::Import && tokens[i].token != Token::From {
+                p += 1;
+                continue;
+            }
+            let Some(&j) = significant.get(p + 1) else {
+                break;
+            };
+            let next = &tokens[j];
+            let Token::Str(ref s) = next.token else {
+                return Err(LexError {
+                    message: "import must be a string".to_string(),
+                    pos: next.pos.clone(),
+                });
+            };
+            if imports.iter().any(|(_, _, exist, _)| *exist == *s) {
+                return Err(LexError {
+                    message: format!("import {} already defined", s),
+                    pos: next.pos.clone(),
+                });
+            }
+            if p != header {
+                return Err(LexError {
+                    message: "import can't be after instruction".to_string(),
+                    pos: next.pos.clone(),
+                });
+            }
+            // from "file" import name, name...
+            let mut end = p + 1;
+            let mut names = None;
+            if tokens[i].token == Token::From {
+                end += 1;
+                if tokens[significant[end]].token != Token::Import {
+                    return Err(LexError {
+                        message: format!("expected 'import' after the file name, found '{}'", tokens[significant[end]].token),
+                        pos: tokens[significant[end]].pos.clone(),
+                    });
+                }
+                let mut selected = Vec::new();
+                loop {
+                    end += 1;
+                    let t = &tokens[significant[end]];
+                    let Token::Ident(ref name) = t.token else {
                         return Err(LexError {
-                            message: format!("import {} already defined", s),
-                            pos: next.pos.clone(),
-                        }
-                        );
+                            message: format!("expected a function name to import, found '{}'", t.token),
+                            pos: t.pos.clone(),
+                        });
+                    };
+                    selected.push((name.clone(), t.pos.clone()));
+                    if tokens[significant[end + 1]].token != Token::Comma {
+                        break;
                     }
-                } else {
+                    end += 1;
+                }
+                names = Some(selected);
+            }
+            imports.push((i, significant[end], s.clone(), names));
+            header = end + 1;
+            p = end + 1;
+        }
+        Ok(imports)
+    }
+
+    // an import statement about to be spliced must start with import or from followed by its
+    // file name, and end before the statement spliced just before it. A grammar change that
+    // moves the tokens would otherwise replace the wrong ones
+    fn check_import_range(tokens: &[LexToken], range: Range<usize>, end: usize, file_name: &str) -> Result<(), LexError> {
+        let error = |message: String, k: usize| LexError {
+            message,
+            pos: tokens.get(k).or(tokens.last()).map(|t| t.pos.clone()).unwrap_or_default(),
+        };
+        if range.is_empty() || range.end > end.min(tokens.len()) {
+            return Err(error(
+                format!("import {} spans tokens {}..{}, out of the statement bounds", file_name, range.start, range.end),
+                range.start,
+            ));
+        }
+        if !matches!(tokens[range.start].token, Token::Import | Token::From) {
+            return Err(error(
+                format!("import {} expected at token {}, found '{}'", file_name, range.start, tokens[range.start].token),
+                range.start,
+            ));
+        }
+        match tokens[range.start + 1..range.end].iter().find(|t| !t.token.is_trivia()) {
+            Some(LexToken { token: Token::Str(s), .. }) if s == file_name => Ok(()),
+            Some(t) => Err(LexError {
+                message: format!("import {} expected a file name after '{}', found '{}'", file_name, tokens[range.start].token, t.token),
+                pos: t.pos.clone(),
+            }),
+            None => Err(error(format!("import {} has no file name", file_name), range.start)),
+        }
+    }
+
+    // keep the selected top level functions of an imported file, and the functions of the file
+    // they call so an imported function can still use its helpers
+    fn select_functions(tokens: &[LexToken], names: &[(String, Position)], file_name: &str) -> Result<Vec<LexToken>, LexError> {
+        let functions = Self::top_level_functions(tokens);
+        let mut pending = Vec::new();
+        for (name, pos) in names {
+            if !functions.iter().any(|(f, _)| f == name) {
+                return Err(LexError {
+                    message: format!("function {} is not defined in {}", name, file_name),
+                    pos: pos.clone(),
+                });
+            }
+            pending.push(name.clone());
+        }
+        let mut kept = HashSet::new();
+        while let Some(name) = pending.pop() {
+            if !kept.insert(name.clone()) {
+                continue;
+            }
+            if let Some((_, range)) = functions.iter().find(|(f, _)| *f == name) {
+                let body: Vec<&LexToken> = tokens[range.clone()].iter().filter(|t| !t.token.is_trivia()).collect();
+                for pair in body.windows(2) {
+                    if pair[0].token == Token::Call
+                        && let Token::Ident(ref callee) = pair[1].token
+                    {
+                        pending.push(callee.clone());
+                    }
+                }
+            }
+        }
+        Ok(functions
+            .into_iter()
+            .filter(|(name, _)| kept.contains(name))
+            .flat_map(|(_, range)| tokens[range].iter().cloned())
+            .collect())
+    }
+
+    // name and token range of each top level function
+    fn top_level_functions(tokens: &[LexToken]) -> Vec<(String, Range<usize>)> {
+        let mut functions = Vec::new();
+        let mut depth = 0;
+        let mut start = None;
+        for (k, t) in tokens.iter().enumerate() {
+            match t.token {
+                Token::Fn if depth == 0 => start = Some(k),
+                Token::LBrace => depth += 1,
+                Token::RBrace => {
+                    depth -= 1;
+                    if depth == 0
+                        && let Some(s) = start.take()
+                        && let Some(name) = tokens[s + 1..].iter().find(|t| !t.token.is_trivia())
+                    {
+                        functions.push((name.token.to_string(), s..k + 1));
+                    }
+                }
+                _ => {}
+            }
+        }
+        functions
+    }
+
+    // drop the functions of an imported file already spliced by another import, the others are
+    // added to `spliced`. The functions its own imports brought were checked when spliced in it
+    fn drop_spliced(tokens: Vec<LexToken>, file_name: &str, spliced: &mut HashSet<(String, String)>) -> Vec<LexToken> {
+        let dropped: Vec<Range<usize>> = Self::top_level_functions(&tokens)
+            .into_iter()
+            .filter(|(_, range)| tokens[range.start].pos.file_name == file_name)
+            .filter(|(name, _)| !spliced.insert((file_name.to_string(), name.clone())))
+            .map(|(_, range)| range)
+            .collect();
+        tokens
+            .into_iter()
+            .enumerate()
+            .filter(|(k, _)| !dropped.iter().any(|range| range.contains(k)))
+            .map(|(_, t)| t)
+            .collect()
+    }
+
+    // an imported file is spliced inline, so at top level it may only declare functions,
+    // a statement there would run in the middle of the importing program
+    fn check_declarations(tokens: &[LexToken], file_name: &str, import_pos: &Position) -> Result<(), LexError> {
+        let mut depth = 0;
+        let mut in_header = false; // between fn and the opening brace of its body
+        for t in tokens {
+            match t.token {
+                Token::LBrace => {
+                    depth += 1;
+                    in_header = false;
+                }
+                Token::RBrace => depth -= 1,
+                Token::Fn if depth == 0 => in_header = true,
+                Token::Eof => {}
+                _ if t.token.is_trivia() => {}
+                _ if depth == 0 && !in_header => {
                     return Err(LexError {
-                        message: format!("import must be a string"),
-                        pos: next.pos.clone(),
+                        message: format!(
+                            "imported file {} can only declare functions, found '{}' at line {}",
+                            file_name, t.token, t.pos.line
+                        ),
+                        pos: import_pos.clone(),
                     });
                 }
+                _ => {}
             }
         }
-        Ok(imports)
+        Ok(())
     }
 
-    fn parse_file(filename: &String,pos: Option<Position>) -> Result<Vec<LexToken>, LexError> {
-        let mut lexer = Lexer::new(filename.clone());
+    // tokens of a file and its count of lines
+    fn parse_file(&mut self, filename: &str,pos: Option<Position>) -> Result<(Vec<LexToken>, usize), LexError> {
+        self.log(1, &format!("lexing {}", filename));
+        let mut lexer = Lexer::with_resolver(filename.to_string(), self.resolver.clone());
+        lexer.options = self.options.clone();
+        lexer.keywords = self.keywords.clone();
         let tokens = lexer.parse(pos)?;
-        Ok(tokens)
+        // a file read again for another import is reported once
+        for warning in lexer.warnings {
+            if !self.warnings.iter().any(|w| w.pos == warning.pos) {
+                self.warnings.push(warning);
+            }
+        }
+        Ok((tokens, Self::line_count(&lexer.src_text)))
     }
 
     fn parse(&mut self, pos: Option<Position>) -> Result<Vec<LexToken>, LexError> {
-        self.src_text = fs::read_to_string(&self.src_filename).map_err(|_|LexError{
-            message:format!("File not found {}",self.src_filename.clone()),
-            pos:pos.unwrap()
+        self.read_source(pos)?;
+        self.scan()
+    }
+
+    fn read_source(&mut self, pos: Option<Position>) -> Result<(), LexError> {
+        // errors are reported at the import statement, or at the start of the main file
+        let pos = pos.unwrap_or_else(|| Position::new(self.src_filename.clone()));
+        self.src_text = self.resolver.read(&self.src_filename).map_err(|e|LexError{
+            message: if e.kind() == ErrorKind::InvalidData {
+                format!("source file is not valid UTF-8 {}",self.src_filename.clone())
+            } else if self.resolver.is_dir(&self.src_filename) {
+                format!("expected a file, found a directory {}", self.src_filename)
+            } else {
+                format!("File not found {}",self.src_filename.clone())
+            },
+            pos
         })?;
+        Ok(())
+    }
 
+    // split the source text into tokens
+    fn scan(&mut self) -> Result<Vec<LexToken>, LexError> {
         let mut tokens = Vec::new();
+        self.scan_into(&mut tokens)?;
+        Ok(tokens)
+    }
+
+    // split the source text into tokens, those read before an error are kept in tokens
+    fn scan_into(&mut self, tokens: &mut Vec<LexToken>) -> Result<(), LexError> {
         loop {
-            self.skip_whitespace();
-            self.skip_comment_single_line();
-            self.skip_comment_multiple_line()?;
+            self.skip_trivia(tokens)?;
             let pos = self.pos.clone();
+            if self.at_null_byte() {
+                return Err(Self::null_byte_error(pos));
+            }
             // end of file
             if self.eof() {
                 tokens.push(LexToken { token: Token::Eof, pos });
@@ -409,12 +976,27 @@ impl Lexer {
             }
             // identify number
             if let Some(word_str) = self.try_number() {
-                if word_str.contains('.') {
+                let word_str = Self::strip_underscores(&word_str).ok_or_else(|| LexError {
+                    message: format!("misplaced '_' in number [{}]", word_str),
+                    pos: pos.clone(),
+                })?;
+                if Self::is_hex(&word_str) {
+                    tokens
+                        .push(LexToken {
+                            token: Token::Float(Self::parse_hex_float(&word_str).ok_or_else(|| {
+                                LexError {
+                                    message: format!("invalid hexadecimal float format [{}]", word_str),
+                                    pos: pos.clone(),
+                                }
+                            })?),
+                            pos,
+                        });
+                } else if word_str.contains('.') || Self::is_scientific(&word_str) {
                     tokens
                         .push(LexToken {
                             token: Token::Float(word_str.parse::<f64>().map_err(|_| {
                                 LexError {
-                                    message: format!("invalid float number format [{}]", word_str),
+                                    message: Self::float_error(&word_str),
                                     pos: pos.clone(),
                                 }
                             })?),
@@ -436,12 +1018,9 @@ impl Lexer {
             }
 
             // identify symbols
-            match self.try_symbol() {
-                Some(token) => {
-                    tokens.push(LexToken { token, pos });
-                    continue;
-                }
-                _ => {}
+            if let Some(token) = self.try_symbol() {
+                tokens.push(LexToken { token, pos });
+                continue;
             }
 
             // identify keyword or an identifier
@@ -449,13 +1028,23 @@ impl Lexer {
             if let Some(word_str) = word {
                 match self.identify_token(&word_str) {
                     Some(token) => {
-                        tokens.push(LexToken { token: token, pos });
+                        tokens.push(LexToken { token, pos });
                         continue;
                     }
                     None => {
-                        if self.is_ident_valid(&word_str) {
+                        if word_str == "_" {
+                            tokens.push(LexToken { token: Token::Underscore, pos });
+                            continue;
+                        } else if self.is_ident_valid(&word_str) {
                             tokens.push(LexToken { token: Token::Ident(word_str), pos });
                             continue;
+                        } else if let Some(k) = word_str.chars().position(|c| c == '\\') {
+                            let mut pos = pos;
+                            pos.bump_col(k);
+                            return Err(LexError {
+                                message: "unexpected '\\' in identifier".to_string(),
+                                pos,
+                            });
                         } else {
                             return Err(LexError {
                                 message: format!("Unknown token [{}]", word_str),
@@ -466,28 +1055,210 @@ impl Lexer {
                 }
             }
         }
-        Ok(tokens)
+        Ok(())
     }
 
-    fn dir_with_sep(path: &str) -> Option<String> {
-        let mut s = Path::new(path).parent()?.to_string_lossy().into_owned();
-        if !s.ends_with(MAIN_SEPARATOR) {
+    // directory of a file with a trailing separator, empty for a file of the current directory
+    fn dir_with_sep(path: &str) -> String {
+        let mut s = Path::new(path).parent().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+        if !s.is_empty() && !s.ends_with(MAIN_SEPARATOR) {
             s.push(MAIN_SEPARATOR);
         }
-        Some(s)
+        s
+    }
+
+    // import paths may be written with '/' or '\' whatever the platform
+    fn normalize_separators(path: &str) -> String {
+        path.replace(['/', '\\'], MAIN_SEPARATOR_STR)
     }
+
+    // a directory imports its index file (main.mpl, or mod.mpl), a file imports itself
+    fn resolve_import(&self, import_name: &str, pos: &Position) -> Result<String, LexError> {
+        if !self.resolver.is_dir(import_name) {
+            return Ok(import_name.to_string());
+        }
+        if self.resolver.is_file(&format!("{}.mpl", import_name)) {
+            return Err(LexError {
+                message: format!("import {} is ambiguous, both {}.mpl and directory {} exist", import_name, import_name, import_name),
+                pos: pos.clone(),
+            });
+        }
+        ["main.mpl", "mod.mpl"]
+            .iter()
+            .map(|index| format!("{}{}{}", import_name.trim_end_matches(MAIN_SEPARATOR), MAIN_SEPARATOR, index))
+            .find(|index| self.resolver.is_file(index))
+            .ok_or_else(|| LexError {
+                message: format!("directory {} has no main.mpl or mod.mpl", import_name),
+                pos: pos.clone(),
+            })
+    }
+
+    // lex a source kept in memory, the positions use the virtual name and the imports are
+    // left as tokens
+    pub fn tokenize_source(src: &str, virtual_name: &str) -> Result<TokenStream, LexError> {
+        let mut lexer = Lexer::new(virtual_name.to_string());
+        lexer.src_text = src.to_string();
+        Ok(TokenStream { tokens: lexer.scan()? })
+    }
+
+    // same as tokenize_source, the comments are kept as tokens
+    pub fn tokenize_source_with_comments(src: &str, virtual_name: &str) -> Result<TokenStream, LexError> {
+        let mut lexer = Lexer::new(virtual_name.to_string());
+        lexer.src_text = src.to_string();
+        lexer.set_keep_trivia(true);
+        Ok(TokenStream { tokens: lexer.scan()? })
+    }
+
+    // lex the named file as far as possible, for the editors. The tokens read before an error
+    // are returned with it, ended by an eof at the error. The imports are left as tokens
+    pub fn tokenize_partial(&mut self) -> (TokenStream, Option<LexError>) {
+        self.reset(self.src_filename.clone(), String::new());
+        let mut tokens = Vec::new();
+        match self.read_source(None).and_then(|_| self.scan_into(&mut tokens)) {
+            Ok(()) => (TokenStream { tokens }, None),
+            Err(e) => {
+                tokens.push(LexToken { token: Token::Eof, pos: e.pos.clone() });
+                (TokenStream { tokens }, Some(e))
+            }
+        }
+    }
+
     pub fn tokenize(&mut self) -> Result<Vec<LexToken>, LexError> {
-        let mut tokens = Self::parse_file(&self.src_filename,None)?; // Parse the main file
-        let working_path=Self::dir_with_sep(&self.src_filename).unwrap_or_else(|| ".".to_string());
-        let mut imports = Self::get_import_list(&tokens)?; // Check imports
-        imports.sort_by_key(|(i, _)| Reverse(*i));// Sort imports by index from the largest index to the smallest
-        for (i,import_filename) in imports { // Tokenize each imported file
-            let import_name = working_path.clone() + &import_filename;
-            let mut imp_tokens = Self::parse_file(&import_name, Some(tokens[i].pos.clone()))?;
+        let start = Instant::now();
+        self.warnings.clear();
+        let (tokens, lines) = self.parse_file(&self.src_filename.clone(),None)?; // Parse the main file
+        self.timings = vec![("lexing", start.elapsed())];
+        self.imports.clear();
+        self.import_graph.clear();
+        self.lines = lines;
+        let (tokens, files) = if self.options.resolve_imports {
+            let start = Instant::now();
+            let main = self.src_filename.clone();
+            let mut chain = vec![main.clone()];
+            let tokens = self.splice_imports(tokens, &main, &mut chain, &mut HashSet::new())?;
+            self.timings.push(("import resolution", start.elapsed()));
+            (tokens, self.import_graph.len())
+        } else {
+            (tokens, 1)
+        };
+        let count = tokens.iter().filter(|t| t.token != Token::Eof).count();
+        self.log(1, &format!("tokenized {} tokens across {} lines from {} files", count, self.lines, files));
+        Ok(tokens)
+    }
+
+    // lines of a source text. A final line break doesn't start a line
+    fn line_count(text: &str) -> usize {
+        let breaks = text.matches('\n').count();
+        if text.is_empty() || text.ends_with('\n') { breaks } else { breaks + 1 }
+    }
+
+    // replace the import statements of a file by the tokens of the files it imports, which have
+    // their own imports spliced first. `chain` is the files being spliced, importing one of them
+    // again is a cycle. `spliced` holds the file and name of the imported functions: a function
+    // reached again, through another path or another import of its file, is spliced once
+    fn splice_imports(
+        &mut self,
+        mut tokens: Vec<LexToken>,
+        file_name: &str,
+        chain: &mut Vec<String>,
+        spliced: &mut HashSet<(String, String)>,
+    ) -> Result<Vec<LexToken>, LexError> {
+        let working_path=Self::dir_with_sep(file_name);
+        let imports = Self::get_import_list(&tokens)?; // Check imports
+        let mut edges = Vec::new();
+        let mut imported = Vec::new();
+        for (i, j, import_filename, names) in imports { // Tokenize each imported file
+            let import_name = self.resolve_import(&(working_path.clone() + &Self::normalize_separators(&import_filename)), &tokens[i].pos)?;
+            self.log(2, &format!("import {} resolved to {}", import_filename, import_name));
+            if chain.contains(&import_name) {
+                return Err(LexError {
+                    message: format!("import cycle {} -> {}", chain.join(" -> "), import_name),
+                    pos: tokens[i].pos.clone(),
+                });
+            }
+            edges.push(import_name.clone());
+            if chain.len() == 1 {
+                self.imports.push(Import { file_name: import_name.clone(), pos: tokens[i].pos.clone() });
+            }
+            let (imp_tokens, imp_lines) = self.parse_file(&import_name, Some(tokens[i].pos.clone()))?;
+            if self.import_graph.contains_key(&import_name) {
+                self.log(2, &format!("{} is already imported, only its functions not spliced yet are added", import_name));
+            } else {
+                self.lines += imp_lines;
+            }
+            chain.push(import_name.clone());
+            let mut imp_tokens = self.splice_imports(imp_tokens, &import_name, chain, spliced)?;
+            chain.pop();
+            Self::check_declarations(&imp_tokens, &import_name, &tokens[i].pos)?;
             imp_tokens.pop(); // remove the eof token
-            // remove import and file name from the main program and insert all the tokens in the import file
-            tokens.splice(i..=i+1, imp_tokens);
+            if let Some(names) = names {
+                imp_tokens = Self::select_functions(&imp_tokens, &names, &import_name)?;
+            }
+            imp_tokens = Self::drop_spliced(imp_tokens, &import_name, spliced);
+            if self.options.import_markers {
+                let pos = tokens[i].pos.clone();
+                imp_tokens.insert(0, LexToken { token: Token::ImportStart(import_name.clone()), pos: pos.clone() });
+                imp_tokens.push(LexToken { token: Token::ImportEnd(import_name.clone()), pos });
+            }
+            imported.push((i, j, import_filename, imp_tokens));
+        }
+        // remove import and file name and insert all the tokens of the imported file, from the
+        // last import so the indexes of the others stay valid
+        let mut end = tokens.len();
+        for (i, j, import_filename, imp_tokens) in imported.into_iter().rev() {
+            Self::check_import_range(&tokens, i..j + 1, end, &import_filename)?;
+            end = i;
+            self.log(2, &format!("splicing {} tokens at index {}", imp_tokens.len(), i));
+            tokens.splice(i..=j, imp_tokens);
         }
+        self.import_graph.insert(file_name.to_string(), edges);
         Ok(tokens)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(src: &str) -> Vec<LexToken> {
+        Lexer::tokenize_source(src, "t.mpl").unwrap().tokens
+    }
+
+    fn check(src: &str, range: Range<usize>, end: usize) -> Result<(), String> {
+        Lexer::check_import_range(&tokens(src), range, end, "f.mpl").map_err(|e| e.message)
+    }
+
+    #[test]
+    fn import_range_valid() {
+        let src = "import \"f.mpl\"\nfn main() {\n}\n";
+        assert_eq!(check(src, 0..2, 2), Ok(()));
+        assert_eq!(check("from \"f.mpl\" import a\nfn main() {\n}\n", 0..4, 4), Ok(()));
+    }
+
+    #[test]
+    fn import_range_not_on_import() {
+        let src = "fn main() {\n}\n";
+        assert_eq!(check(src, 0..2, 6), Err("import f.mpl expected at token 0, found 'fn'".to_string()));
+    }
+
+    #[test]
+    fn import_range_without_file_name() {
+        let src = "import \"g.mpl\"\nfn main() {\n}\n";
+        assert_eq!(
+            check(src, 0..2, 2),
+            Err("import f.mpl expected a file name after 'import', found '\"g.mpl\"'".to_string())
+        );
+        let src = "import main\nfn main() {\n}\n";
+        assert_eq!(check(src, 0..2, 2), Err("import f.mpl expected a file name after 'import', found 'main'".to_string()));
+        assert_eq!(check(src, 0..1, 2), Err("import f.mpl has no file name".to_string()));
+    }
+
+    #[test]
+    fn import_range_out_of_bounds() {
+        let src = "import \"f.mpl\"\nfn main() {\n}\n";
+        // past the next import statement, or past the end of the tokens
+        assert_eq!(check(src, 0..2, 1), Err("import f.mpl spans tokens 0..2, out of the statement bounds".to_string()));
+        assert_eq!(check(src, 0..20, 20), Err("import f.mpl spans tokens 0..20, out of the statement bounds".to_string()));
+        assert_eq!(check(src, 1..1, 2), Err("import f.mpl spans tokens 1..1, out of the statement bounds".to_string()));
+    }
+}
@@ -0,0 +1,50 @@
+pub mod ast;
+pub mod error;
+pub mod eval;
+pub mod fold;
+pub mod highlight;
+pub mod import;
+pub mod lexer;
+pub mod lint;
+pub mod parser;
+pub mod resolve;
+pub mod token;
+pub mod typeck;
+
+pub use error::MplError;
+
+use std::str::FromStr;
+
+use ast::Program;
+use eval::Interpreter;
+use lexer::Lexer;
+use parser::Parser;
+use resolve::Resolver;
+use typeck::TypeChecker;
+
+// parse and check a program kept in memory, the name is used for the positions. Its imports
+// are not resolved
+pub fn parse_str(src: &str, name: &str) -> Result<Program, MplError> {
+    let program = Parser::from_stream(Lexer::tokenize_source(src, name)?).parse_tokens()?;
+    Resolver::new().resolve(&program)?;
+    TypeChecker::new(false).check(&program)?;
+    Ok(program)
+}
+
+// let program: Program = src.parse()?, the positions are in <string>
+impl FromStr for Program {
+    type Err = MplError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        parse_str(src, "<string>")
+    }
+}
+
+// run a program kept in memory, what it prints on stdout is returned
+pub fn run_str(src: &str, name: &str) -> Result<String, MplError> {
+    let mut program = parse_str(src, name)?;
+    fold::fold_program(&mut program);
+    let mut out = Vec::new();
+    Interpreter::with_output(Box::new(&mut out), Box::new(std::io::stderr())).run(&program)?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
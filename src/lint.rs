@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::ast::{Block, Program, StmtKind};
+use crate::lexer::Position;
+
+// Diagnostic that doesn't stop the compilation (unless warnings are denied)
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub message: String,
+    pub pos: Position,
+}
+
+// Format how a warning is displayed
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Warning : [{}] at {} ({}:{})",
+            self.message, self.pos.file_name, self.pos.line, self.pos.col
+        )
+    }
+}
+
+// collect the names of the functions called in a block
+fn collect_calls<'a>(block: &'a Block, calls: &mut HashSet<&'a str>) {
+    for stmt in block {
+        match &stmt.kind {
+            StmtKind::Call { name, .. } => {
+                calls.insert(name);
+            }
+            StmtKind::If { then, otherwise, .. } => {
+                collect_calls(then, calls);
+                if let Some(otherwise) = otherwise {
+                    collect_calls(otherwise, calls);
+                }
+            }
+            StmtKind::For { body, .. } | StmtKind::While { body, .. } => collect_calls(body, calls),
+            StmtKind::Fn(function) => collect_calls(&function.body, calls),
+            _ => {}
+        }
+    }
+}
+
+// an import is unused when none of its functions is called from another file
+pub fn unused_imports(program: &Program) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for import in &program.imports {
+        let mut calls = HashSet::new();
+        for function in &program.functions {
+            if function.pos.file_name != import.file_name {
+                collect_calls(&function.body, &mut calls);
+            }
+        }
+        let used = program
+            .functions
+            .iter()
+            .any(|f| f.pos.file_name == import.file_name && calls.contains(f.name.as_str()));
+        if !used {
+            warnings.push(Warning {
+                message: format!("unused import {}", import.file_name),
+                pos: import.pos.clone(),
+            });
+        }
+    }
+    warnings
+}
@@ -1,9 +1,184 @@
-mod lexer;
-mod token;
-mod parser;
-
 use std::env;
-use parser::Parser;
+use std::io::{self, BufRead};
+use std::time::Duration;
+use mpl2::ast::Stmt;
+use mpl2::eval::Interpreter;
+use mpl2::fold;
+use mpl2::highlight;
+use mpl2::lexer::{Lexer, LexerOptions, TokenStream};
+use mpl2::lint::{self, Warning};
+use mpl2::parser::Parser;
+use mpl2::resolve::Resolver;
+use mpl2::typeck::TypeChecker;
+
+const USAGE: &str = "Usage: mpl [--check] [--lex-only | --parse-only] [--deny-warnings] [--strict] [--warn-shadow] [--no-tabs] [--tokens] [--highlight] [--import-markers] [--no-imports] [--stats] [--ast] [--parse-trace] [--time] [--max-errors N] [--entry NAME] [--encoding utf-8] [-v | -vv] (--repl | <source_filename>)";
+
+// Command line options
+struct Options {
+    src_filename: String,
+    check: bool, // report the diagnostics without running the program
+    lex_only: bool, // stop after the tokenization
+    parse_only: bool, // stop after the parsing
+    deny_warnings: bool, // any warning makes the run fail
+    strict: bool, // no implicit int to float promotion
+    warn_shadow: bool, // warn when a let hides a variable of an enclosing block
+    no_tabs: bool, // warn on each tab outside the strings
+    tokens: bool, // print the token stream
+    highlight: bool, // print the source colored by token kind
+    import_markers: bool, // show where the imported files start and end in the token stream
+    no_imports: bool, // lex the source file alone, its imports are left as tokens
+    stats: bool, // print the number of tokens of each kind
+    ast: bool, // print the AST as S-expressions
+    parse_trace: bool, // log each grammar rule entered and left
+    time: bool, // print the duration of each phase
+    max_errors: usize, // grammar errors reported before giving up
+    verbosity: u8, // 1 logs the phases, 2 also logs the import resolution steps
+    entry: String, // function run first
+    repl: bool, // run the statements read from stdin line by line instead of a file
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut args = env::args();
+    let _program = args.next(); // skip program name
+    let mut src_filename = None;
+    let mut check = false;
+    let mut lex_only = false;
+    let mut parse_only = false;
+    let mut deny_warnings = false;
+    let mut strict = false;
+    let mut warn_shadow = false;
+    let mut no_tabs = false;
+    let mut tokens = false;
+    let mut highlight = false;
+    let mut import_markers = false;
+    let mut no_imports = false;
+    let mut stats = false;
+    let mut ast = false;
+    let mut parse_trace = false;
+    let mut time = false;
+    let mut max_errors = 20;
+    let mut verbosity = 0;
+    let mut entry = "main".to_string();
+    let mut repl = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--lex-only" => lex_only = true,
+            "--parse-only" => parse_only = true,
+            "--deny-warnings" => deny_warnings = true,
+            "--strict" => strict = true,
+            "--warn-shadow" => warn_shadow = true,
+            "--no-tabs" => no_tabs = true,
+            "--tokens" => tokens = true,
+            "--highlight" => highlight = true,
+            "--import-markers" => import_markers = true,
+            "--no-imports" => no_imports = true,
+            "--stats" => stats = true,
+            "--ast" => ast = true,
+            "--parse-trace" => parse_trace = true,
+            "--time" => time = true,
+            "--max-errors" => {
+                max_errors = args
+                    .next()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or(format!("--max-errors expects a positive number\n{}", USAGE))?
+            }
+            "--entry" => entry = args.next().ok_or(format!("--entry expects a function name\n{}", USAGE))?,
+            // the sources are read as UTF-8, the only encoding supported for now
+            "--encoding" => {
+                let encoding = args.next().ok_or(format!("--encoding expects an encoding name\n{}", USAGE))?;
+                if !encoding.eq_ignore_ascii_case("utf-8") && !encoding.eq_ignore_ascii_case("utf8") {
+                    return Err(format!("unsupported encoding {}, only utf-8 is supported", encoding));
+                }
+            }
+            "--repl" => repl = true,
+            "-v" => verbosity = 1,
+            "-vv" => verbosity = 2,
+            _ if arg.starts_with('-') => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            _ if src_filename.is_none() => src_filename = Some(arg),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    if lex_only && parse_only {
+        return Err(format!("--lex-only and --parse-only can't be used together\n{}", USAGE));
+    }
+    Ok(Options {
+        // get source filename, the REPL doesn't need one
+        src_filename: match src_filename {
+            Some(src_filename) => src_filename,
+            None if repl => String::new(),
+            None => return Err(USAGE.to_string()),
+        },
+        check,
+        lex_only,
+        parse_only,
+        deny_warnings,
+        strict,
+        warn_shadow,
+        no_tabs,
+        tokens,
+        highlight,
+        import_markers,
+        no_imports,
+        stats,
+        ast,
+        parse_trace,
+        time,
+        max_errors,
+        verbosity,
+        entry,
+        repl,
+    })
+}
+
+// read statements from stdin, one line at a time, the variables are kept from line to line
+fn repl(options: &Options) -> Result<(), Box<dyn std::error::Error>> {
+    let mut parser = Parser::new();
+    let mut resolver = Resolver::new();
+    let mut checker = TypeChecker::new(options.strict);
+    let mut interpreter = Interpreter::new();
+    let mut lexer = Lexer::new(String::new());
+    let stdin = io::stdin();
+    for number in 1.. {
+        eprint!("> ");
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break; // end of input
+        }
+        // each line is named after its number so an error tells the line it is in
+        lexer.reset(format!("<repl:{}>", number), line);
+        let tokens = match lexer.tokenize_text() {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                eprint!("{e}");
+                continue;
+            }
+        };
+        if options.tokens {
+            print!("{}", tokens);
+        }
+        let block = match parser.parse_statements(tokens.tokens) {
+            Ok(block) => block,
+            Err(e) => {
+                eprint!("{e}");
+                continue;
+            }
+        };
+        if options.ast {
+            println!("{}", block.iter().map(Stmt::to_sexpr).collect::<Vec<_>>().join(" "));
+        }
+        let result: Result<(), Box<dyn std::error::Error>> = resolver
+            .resolve_statements(&block)
+            .map_err(Into::into)
+            .and_then(|_| checker.check_statements(&block).map_err(Into::into))
+            .and_then(|_| interpreter.exec_statements(&block).map_err(Into::into));
+        if let Err(e) = result {
+            eprint!("{e}");
+        }
+    }
+    Ok(())
+}
 
 fn main() {
     if let Err(e) = real_main() {
@@ -13,10 +188,94 @@ fn main() {
     }
 }
 fn real_main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut args = env::args();
-    let _program = args.next(); // skip program name
-    let main_src_filename = args.next().ok_or_else(|| "Usage: mpl <source_filename>")?; // get source filename
+    let options = parse_args()?;
+    if options.repl {
+        return repl(&options);
+    }
+    let log = |message: &str| {
+        if options.verbosity >= 1 {
+            eprintln!("{}", message);
+        }
+    };
+    if options.highlight {
+        let mut lex = Lexer::new(options.src_filename.clone());
+        print!("{}", highlight::highlight(lex.source_text()?, &options.src_filename)?);
+        return Ok(());
+    }
+    let lexer_options = LexerOptions {
+        verbosity: options.verbosity,
+        import_markers: options.import_markers,
+        resolve_imports: !options.no_imports,
+        warn_tabs: options.no_tabs,
+        ..LexerOptions::default()
+    };
+    if options.lex_only {
+        let mut lex = Lexer::new(options.src_filename.clone());
+        lex.set_options(lexer_options);
+        let tokens = TokenStream { tokens: lex.tokenize()? };
+        for warning in lex.warnings() {
+            eprint!("{warning}");
+        }
+        if options.tokens {
+            println!("{}", tokens);
+        }
+        if options.time {
+            print_timings(lex.timings());
+        }
+        return Ok(());
+    }
     let mut p = Parser::new();
-    p.parse(main_src_filename)?;
+    p.set_show_tokens(options.tokens);
+    p.set_lexer_options(lexer_options);
+    p.set_max_errors(options.max_errors);
+    p.set_verbosity(options.verbosity);
+    p.set_entry(options.entry.clone());
+    p.set_trace(options.parse_trace);
+    let mut program = p.parse(options.src_filename.clone())?;
+    if options.stats {
+        let ts = TokenStream { tokens: p.tokens().to_vec() };
+        for (kind, count) in ts.stats() {
+            println!("{:>6} {}", count, kind);
+        }
+    }
+    if options.ast {
+        println!("{}", program.to_sexpr());
+    }
+    if options.parse_only {
+        if options.time {
+            print_timings(p.timings());
+        }
+        return Ok(());
+    }
+    log("resolving names");
+    let mut resolver = Resolver::new();
+    resolver.set_warn_shadow(options.warn_shadow);
+    resolver.resolve(&program)?;
+    log("checking types");
+    TypeChecker::new(options.strict).check(&program)?;
+    log("linting");
+    let mut warnings: Vec<Warning> = p.warnings().to_vec();
+    warnings.extend_from_slice(resolver.warnings());
+    warnings.extend(lint::unused_imports(&program));
+    for warning in &warnings {
+        eprint!("{warning}");
+    }
+    if options.deny_warnings && !warnings.is_empty() {
+        return Err(format!("{} warning(s) treated as errors", warnings.len()).into());
+    }
+    if !options.check {
+        log(&format!("running {}", options.entry));
+        fold::fold_program(&mut program);
+        Interpreter::new().run_entry(&program, &options.entry)?;
+    }
+    if options.time {
+        print_timings(p.timings());
+    }
     Ok(())
 }
+
+fn print_timings(timings: &[(&'static str, Duration)]) {
+    for (phase, duration) in timings {
+        eprintln!("{}: {:.3} ms", phase, duration.as_secs_f64() * 1000.0);
+    }
+}
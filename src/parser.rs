@@ -1,11 +1,33 @@
 use std::{fmt,error};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-use crate::lexer::{LexError, LexToken, Lexer, Position, TokenStream};
+use crate::ast::{self, Arg, BinaryOp, Block, Builtin, Expr, ExprKind, Function, NodeId, Param, Program, Stmt, StmtKind, Type, UnaryOp};
+use crate::lexer::{LexError, LexToken, Lexer, LexerOptions, Position, TokenStream};
+use crate::lint::Warning;
 use crate::token::Token;
 
 
+// Default maximum nesting of expressions
+pub const MAX_DEPTH: usize = 256;
+
 pub struct Parser {
-    tokens: Vec<LexToken>
+    tokens: Vec<LexToken>,
+    cur: usize, // index of the current token
+    depth: usize, // current expression nesting
+    max_depth: usize, // maximum expression nesting
+    show_tokens: bool, // print the token stream before parsing
+    lexer_options: LexerOptions, // settings of the lexing done by parse
+    loop_depth: usize, // number of loops around the current statement
+    timings: Vec<(&'static str, Duration)>, // duration of each phase of the last parse
+    max_errors: usize, // errors collected before giving up, more than 1 recovers at the next function
+    verbosity: u8, // log the lexing and parsing steps to stderr
+    entry: String, // function the program must define, main by default
+    trace: bool, // log the entry and exit of each grammar rule to stderr
+    trace_depth: usize, // rules being parsed, to indent the trace
+    has_imports: bool, // the main file imports others, so it declares something even without functions
+    next_node: usize, // id of the next node of the REPL, the ids of the lines don't overlap
+    warnings: Vec<Warning>, // warnings of the lexing of the last parse
 }
 
 #[derive(Debug)]
@@ -13,9 +35,18 @@ pub enum ParseError {
     Lex(LexError),
     Unexpected {
         found: Token,
-        expected: &'static str,
+        expected: Vec<&'static str>,
         pos: Position,
-    }
+    },
+    Syntax {
+        message: String,
+        pos: Position,
+    },
+    // errors of several functions, truncated when the cap was hit
+    Many {
+        errors: Vec<ParseError>,
+        truncated: bool,
+    },
 }
 
 impl From<LexError> for ParseError {
@@ -33,35 +64,1094 @@ impl fmt::Display for ParseError {
                 found,
                 expected,
                 pos,
-            } => write!(
+            } => {
+                let expected = match expected.as_slice() {
+                    [one] => one.to_string(),
+                    several => format!("one of {}", several.join(", ")),
+                };
+                let found = match found {
+                    Token::Eof => found.to_string(),
+                    _ => format!("'{}'", found),
+                };
+                writeln!(
+                    f,
+                    "Grammar error : Expected {}, found {} at {} line:col -> ({}:{})",
+                    expected, found, pos.file_name, pos.line, pos.col,
+                )
+            }
+            Self::Syntax { message, pos } => writeln!(
                 f,
-                "Grammar error : Expected {}, found {:?} at {} line:col -> ({}:{})\n",
-                expected, found, pos.file_name, pos.line, pos.col,
+                "Grammar error : {} at {} line:col -> ({}:{})",
+                message, pos.file_name, pos.line, pos.col,
             ),
+            Self::Many { errors, truncated } => {
+                for e in errors {
+                    write!(f, "{}", e)?;
+                }
+                if *truncated {
+                    writeln!(f, "\u{2026} and more errors")?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
 impl error::Error for ParseError {}
 
+impl ParseError {
+    // source position of the error, whatever the variant
+    pub fn position(&self) -> &Position {
+        match self {
+            Self::Lex(e) => &e.pos,
+            Self::Unexpected { pos, .. } | Self::Syntax { pos, .. } => pos,
+            Self::Many { errors, .. } => errors[0].position(),
+        }
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Parser {
     pub fn new() -> Self {
         Self {
-            tokens: Vec::new()
+            tokens: Vec::new(),
+            cur: 0,
+            depth: 0,
+            max_depth: MAX_DEPTH,
+            show_tokens: false,
+            lexer_options: LexerOptions::default(),
+            loop_depth: 0,
+            timings: Vec::new(),
+            max_errors: 1,
+            verbosity: 0,
+            entry: "main".to_string(),
+            trace: false,
+            trace_depth: 0,
+            has_imports: false,
+            next_node: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    // require the given entry function instead of main
+    pub fn set_entry(&mut self, entry: String) {
+        self.entry = entry;
+    }
+
+    // 1 logs the parsing phase. The lexing is logged at the verbosity of the lexer options
+    pub fn set_verbosity(&mut self, verbosity: u8) {
+        self.verbosity = verbosity;
+    }
+
+    // duration of the lexing, import resolution and parsing phases of the last parse
+    pub fn timings(&self) -> &[(&'static str, Duration)] {
+        &self.timings
+    }
+
+    // tokens of the last parse, imports spliced in
+    pub fn tokens(&self) -> &[LexToken] {
+        &self.tokens
+    }
+
+    // print the token stream before parsing
+    pub fn set_show_tokens(&mut self, show_tokens: bool) {
+        self.show_tokens = show_tokens;
+    }
+
+    // settings of the lexer used by parse. Without resolving the imports the grammar rejects
+    // the import statements, and the comments kept as tokens are dropped before parsing
+    pub fn set_lexer_options(&mut self, lexer_options: LexerOptions) {
+        self.lexer_options = lexer_options;
+    }
+
+    // warnings of the last parse
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    // collect up to max_errors errors, skipping to the next function after each one
+    pub fn set_max_errors(&mut self, max_errors: usize) {
+        self.max_errors = max_errors.max(1);
+    }
+
+    // log the entry and exit of each grammar rule with the current token to stderr
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    // change the maximum expression nesting
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    // run a grammar rule, logging its entry and exit when tracing. The rules entered at each
+    // level of a nested expression call their body directly when not tracing, a deep nesting
+    // must fit the stack of a spawned thread
+    fn traced<T>(&mut self, rule: &'static str, parse: impl FnOnce(&mut Self) -> Result<T, ParseError>) -> Result<T, ParseError> {
+        if !self.trace {
+            return parse(self);
+        }
+        let indent = "  ".repeat(self.trace_depth);
+        eprintln!("{}enter {} @ {:?}", indent, rule, self.peek().token);
+        self.trace_depth += 1;
+        let result = parse(self);
+        self.trace_depth -= 1;
+        let outcome = if result.is_ok() { "exit" } else { "fail" };
+        eprintln!("{}{} {} @ {:?}", indent, outcome, rule, self.peek().token);
+        result
+    }
+
+    // get the current token (the last token is always Eof)
+    fn peek(&self) -> &LexToken {
+        &self.tokens[self.cur.min(self.tokens.len() - 1)]
+    }
+
+    // check the current token without consuming it
+    fn check(&self, token: &Token) -> bool {
+        self.peek().token == *token
+    }
+
+    // consume the current token
+    fn advance(&mut self) -> LexToken {
+        let t = self.peek().clone();
+        if self.cur < self.tokens.len() {
+            self.cur += 1;
+        }
+        t
+    }
+
+    // build an error on the current token
+    fn unexpected(&self, expected: &'static str) -> ParseError {
+        self.unexpected_one_of(&[expected])
+    }
+
+    // build an error on the current token when several tokens were valid. A closing bracket
+    // found where another one closes the open bracket is likely a typo, the error suggests it
+    fn unexpected_one_of(&self, expected: &[&'static str]) -> ParseError {
+        let t = self.peek();
+        if matches!(t.token, Token::RParen | Token::RBracket | Token::RBrace)
+            && let Some(closing) = expected.iter().find(|e| ["')'", "']'", "'}'"].contains(e))
+        {
+            return ParseError::Syntax {
+                message: format!("found '{}', did you mean {}?", t.token, closing),
+                pos: t.pos.clone(),
+            };
+        }
+        ParseError::Unexpected {
+            found: t.token.clone(),
+            expected: expected.to_vec(),
+            pos: t.pos.clone(),
+        }
+    }
+
+    // targeted error for a loop keyword found where a statement or an expression starts
+    fn misplaced_loop_keyword(&self) -> Option<ParseError> {
+        let t = self.peek();
+        let message = match t.token {
+            Token::To | Token::Step => format!("'{}' is only valid inside a for loop", t.token),
+            Token::Next => "'next' is only valid as a statement inside a loop".to_string(),
+            _ => return None,
+        };
+        Some(ParseError::Syntax {
+            message,
+            pos: t.pos.clone(),
+        })
+    }
+
+    // consume the current token if it is the expected one
+    fn expect(&mut self, token: Token, expected: &'static str) -> Result<LexToken, ParseError> {
+        if self.check(&token) {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    // check if the current token starts an expression on the given line, statements have no
+    // separator so an expression on the next line belongs to the next statement
+    fn starts_expr_on_line(&self, line: usize) -> bool {
+        let t = self.peek();
+        t.pos.line == line
+            && matches!(
+                t.token,
+                Token::Integer(_)
+                    | Token::Float(_)
+                    | Token::Str(_)
+                    | Token::True
+                    | Token::False
+                    | Token::Nl
+                    | Token::Ident(_)
+                    | Token::ToStr
+                    | Token::ToInt
+                    | Token::ToFloat
+                    | Token::Len
+                    | Token::LParen
+                    | Token::LBracket
+                    | Token::Minus
+            )
+    }
+
+    // consume an identifier
+    fn expect_ident(&mut self, expected: &'static str) -> Result<(String, Position), ParseError> {
+        if let Token::Ident(name) = &self.peek().token {
+            let name = name.clone();
+            let t = self.advance();
+            Ok((name, t.pos))
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    // program := function* eof
+    fn parse_program(&mut self) -> Result<Program, ParseError> {
+        let mut functions: Vec<Function> = Vec::new();
+        let mut errors = Vec::new();
+        while !self.check(&Token::Eof) {
+            let start = self.cur;
+            let result = self.parse_function().and_then(|function| {
+                if functions.iter().any(|f| f.name == function.name) {
+                    return Err(ParseError::Syntax {
+                        message: format!("function {} already defined", function.name),
+                        pos: function.pos,
+                    });
+                }
+                Ok(function)
+            });
+            match result {
+                Ok(function) => functions.push(function),
+                Err(e) if self.max_errors == 1 => return Err(e),
+                Err(_) if errors.len() == self.max_errors => {
+                    return Err(ParseError::Many { errors, truncated: true });
+                }
+                Err(e) => {
+                    errors.push(e);
+                    self.synchronize(start);
+                }
+            }
+        }
+        match errors.len() {
+            0 => {}
+            1 => return Err(errors.remove(0)),
+            _ => return Err(ParseError::Many { errors, truncated: false }),
+        }
+        // a file with only imports of empty files is missing its main, not its declarations
+        if functions.is_empty() && !self.has_imports {
+            return Err(ParseError::Syntax {
+                message: "source file contains no declarations".to_string(),
+                pos: self.peek().pos.clone(),
+            });
+        }
+        if !functions.iter().any(|f| f.name == self.entry) {
+            return Err(ParseError::Syntax {
+                message: format!("{} function not found", self.entry),
+                pos: self.peek().pos.clone(),
+            });
+        }
+        let mut program = Program {
+            functions,
+            imports: Vec::new(),
+            node_count: 0,
+        };
+        program.number_nodes();
+        Ok(program)
+    }
+
+    // skip to the next function declaration after an error in the function starting at `start`,
+    // a missing closing brace leaves the current token on the next 'fn'
+    fn synchronize(&mut self, start: usize) {
+        if self.cur == start {
+            self.advance();
+        }
+        self.depth = 0;
+        self.loop_depth = 0;
+        while !self.check(&Token::Fn) && !self.check(&Token::Eof) {
+            self.advance();
+        }
+    }
+
+    // function := 'fn' (main | ident) '(' params ')' block
+    fn parse_function(&mut self) -> Result<Function, ParseError> {
+        self.traced("parse_function", Self::parse_function_inner)
+    }
+
+    fn parse_function_inner(&mut self) -> Result<Function, ParseError> {
+        let pos = self.expect(Token::Fn, "function declaration")?.pos;
+        let name = if self.check(&Token::Main) {
+            self.advance();
+            "main".to_string()
+        } else {
+            self.expect_ident("function name")?.0
+        };
+        self.expect(Token::LParen, "'('")?;
+        let mut params = Vec::new();
+        if !self.check(&Token::RParen) {
+            loop {
+                let (name, pos) = self.expect_ident("parameter name")?;
+                self.expect(Token::Colon, "':'")?;
+                let ty = self.parse_type()?;
+                params.push(Param { name, ty, pos });
+                if self.check(&Token::Comma) {
+                    self.advance();
+                } else if self.check(&Token::RParen) {
+                    break;
+                } else {
+                    return Err(self.unexpected_one_of(&["','", "')'"]));
+                }
+            }
+        }
+        self.expect(Token::RParen, "')'")?;
+        let body = self.parse_block()?;
+        Ok(Function {
+            name,
+            params,
+            body,
+            pos,
+        })
+    }
+
+    // type := 'int' | 'float' | '[' type ']'
+    fn parse_type(&mut self) -> Result<Type, ParseError> {
+        self.traced("parse_type", Self::parse_type_inner)
+    }
+
+    fn parse_type_inner(&mut self) -> Result<Type, ParseError> {
+        match self.peek().token {
+            Token::IntType => {
+                self.advance();
+                Ok(Type::Int)
+            }
+            Token::FloatType => {
+                self.advance();
+                Ok(Type::Float)
+            }
+            Token::LBracket => {
+                self.advance();
+                let elem = self.parse_type()?;
+                self.expect(Token::RBracket, "']'")?;
+                Ok(Type::Array(Box::new(elem)))
+            }
+            _ => Err(self.unexpected("type")),
+        }
+    }
+
+    // block := '{' statement* '}'
+    fn parse_block(&mut self) -> Result<Block, ParseError> {
+        self.traced("parse_block", Self::parse_block_inner)
+    }
+
+    fn parse_block_inner(&mut self) -> Result<Block, ParseError> {
+        self.expect(Token::LBrace, "'{'")?;
+        let block = self.parse_statements_until_brace()?;
+        self.expect(Token::RBrace, "'}'")?;
+        Ok(block)
+    }
+
+    // statements up to a closing brace or the end of file
+    fn parse_statements_until_brace(&mut self) -> Result<Block, ParseError> {
+        let mut block = Vec::new();
+        while !self.check(&Token::RBrace) && !self.check(&Token::Eof) {
+            if self.check(&Token::Let) || self.check(&Token::Local) {
+                block.extend(self.parse_let()?);
+            } else {
+                block.push(self.parse_statement()?);
+            }
+        }
+        Ok(block)
+    }
+
+    fn parse_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.traced("parse_statement", Self::parse_statement_inner)
+    }
+
+    fn parse_statement_inner(&mut self) -> Result<Stmt, ParseError> {
+        let pos = self.peek().pos.clone();
+        let kind = match self.peek().token {
+            Token::Print | Token::Println | Token::Eprint | Token::Eprintln => {
+                let token = self.advance().token;
+                let newline = token == Token::Println || token == Token::Eprintln;
+                let to_stderr = token == Token::Eprint || token == Token::Eprintln;
+                // println alone prints a blank line, print alone has nothing to print
+                let mut args = Vec::new();
+                if self.starts_expr_on_line(pos.line) {
+                    args.push(self.parse_expr()?);
+                    while self.check(&Token::Comma) {
+                        let comma = self.advance().pos;
+                        if !self.starts_expr_on_line(comma.line) {
+                            return Err(ParseError::Syntax {
+                                message: format!("trailing ',' after the values of {}", token),
+                                pos: comma,
+                            });
+                        }
+                        args.push(self.parse_expr()?);
+                    }
+                } else if !newline {
+                    return Err(ParseError::Syntax {
+                        message: format!("{} expects a value", if to_stderr { "eprint" } else { "print" }),
+                        pos,
+                    });
+                }
+                let precision = if args.is_empty() { None } else { self.parse_precision()? };
+                StmtKind::Print {
+                    args,
+                    newline,
+                    precision,
+                    to_stderr,
+                }
+            }
+            Token::Call => {
+                self.advance();
+                let (name, _) = self.expect_ident("function name")?;
+                let args = self.parse_args()?;
+                StmtKind::Call { name, args }
+            }
+            Token::If => self.parse_if()?,
+            Token::For => self.parse_for()?,
+            Token::While => {
+                self.advance();
+                let cond = self.parse_expr()?;
+                let body = self.parse_loop_body()?;
+                StmtKind::While { cond, body }
+            }
+            Token::Break | Token::Next => {
+                let token = self.advance().token;
+                let keyword = if token == Token::Break { "break" } else { "next" };
+                if self.loop_depth == 0 {
+                    return Err(ParseError::Syntax {
+                        message: format!("{} outside of loop", keyword),
+                        pos,
+                    });
+                }
+                if token == Token::Next {
+                    StmtKind::Next
+                } else if self.starts_expr_on_line(pos.line) {
+                    StmtKind::Break(Some(self.parse_expr()?))
+                } else {
+                    StmtKind::Break(None)
+                }
+            }
+            Token::Fn => {
+                // the loops around the declaration don't enclose its body
+                let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+                let function = self.parse_function();
+                self.loop_depth = loop_depth;
+                StmtKind::Fn(Rc::new(function?))
+            }
+            Token::Ident(ref name) => {
+                let name = name.clone();
+                self.advance();
+                self.expect(Token::Equal, "'='")?;
+                let value = self.parse_expr()?;
+                StmtKind::Assign { name, value }
+            }
+            _ => return Err(self.misplaced_loop_keyword().unwrap_or_else(|| self.unexpected("statement"))),
+        };
+        Ok(Stmt { kind, pos, id: NodeId::default() })
+    }
+
+    // precision := (':' integer)?
+    fn parse_precision(&mut self) -> Result<Option<usize>, ParseError> {
+        self.traced("parse_precision", Self::parse_precision_inner)
+    }
+
+    fn parse_precision_inner(&mut self) -> Result<Option<usize>, ParseError> {
+        if !self.check(&Token::Colon) {
+            return Ok(None);
+        }
+        self.advance();
+        match self.peek().token {
+            Token::Integer(n) => {
+                self.advance();
+                Ok(Some(n as usize))
+            }
+            Token::Minus => Err(ParseError::Syntax {
+                message: "format precision can't be negative".to_string(),
+                pos: self.peek().pos.clone(),
+            }),
+            _ => Err(self.unexpected("format precision")),
+        }
+    }
+
+    // let := ('let' | 'local') binding '=' expr (',' binding '=' expr)*
+    //      | ('let' | 'local') binding (',' binding)+ '=' expr (',' expr)*
+    // the second form takes one value for all the variables or one value per variable,
+    // each variable gives its own let statement
+    //      | ('let' | 'local') binding (',' binding)*
+    // without a value every variable needs a type, and must be assigned before use
+    fn parse_let(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        self.traced("parse_let", Self::parse_let_inner)
+    }
+
+    fn parse_let_inner(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let pos = self.peek().pos.clone();
+        let local = self.advance().token == Token::Local;
+        let mut bindings = vec![self.parse_binding()?];
+        while self.check(&Token::Comma) {
+            self.advance();
+            bindings.push(self.parse_binding()?);
+        }
+        if !self.check(&Token::Equal) {
+            let mut stmts = Vec::new();
+            for (name, ty, pos) in bindings {
+                if ty.is_none() {
+                    return Err(ParseError::Syntax {
+                        message: format!("variable {} declared without a value needs a type", name),
+                        pos,
+                    });
+                }
+                stmts.push(Stmt {
+                    kind: StmtKind::Let {
+                        name,
+                        ty,
+                        value: None,
+                        local,
+                    },
+                    pos,
+                    id: NodeId::default(),
+                });
+            }
+            return Ok(stmts);
         }
+        self.advance();
+        let mut values = vec![self.parse_expr()?];
+        if bindings.len() == 1 {
+            while self.check(&Token::Comma) {
+                self.advance();
+                bindings.push(self.parse_binding()?);
+                self.expect(Token::Equal, "'='")?;
+                values.push(self.parse_expr()?);
+            }
+        } else {
+            while self.check(&Token::Comma) {
+                self.advance();
+                values.push(self.parse_expr()?);
+            }
+            if values.len() != 1 && values.len() != bindings.len() {
+                return Err(ParseError::Syntax {
+                    message: format!("{} variables declared but {} values given", bindings.len(), values.len()),
+                    pos,
+                });
+            }
+        }
+        Ok(bindings
+            .into_iter()
+            .enumerate()
+            .map(|(k, (name, ty, pos))| Stmt {
+                kind: StmtKind::Let {
+                    name,
+                    ty,
+                    value: Some(values[k.min(values.len() - 1)].clone()),
+                    local,
+                },
+                pos,
+                id: NodeId::default(),
+            })
+            .collect())
+    }
+
+    // binding := ident (':' type)?
+    fn parse_binding(&mut self) -> Result<(String, Option<Type>, Position), ParseError> {
+        let (name, pos) = self.expect_ident("variable name")?;
+        let ty = if self.check(&Token::Colon) {
+            self.advance();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        Ok((name, ty, pos))
+    }
+
+    // if := ('if' | 'elif') expr block ('elif' if | 'else' 'if' if | 'else' block)?
+    fn parse_if(&mut self) -> Result<StmtKind, ParseError> {
+        self.traced("parse_if", Self::parse_if_inner)
+    }
+
+    fn parse_if_inner(&mut self) -> Result<StmtKind, ParseError> {
+        self.advance(); // skip 'if' or 'elif'
+        let cond = self.parse_expr()?;
+        let then = self.parse_block()?;
+        let otherwise = match self.peek().token {
+            Token::Elif => Some(self.parse_else_if()?),
+            Token::Else => {
+                self.advance();
+                if self.check(&Token::If) {
+                    Some(self.parse_else_if()?)
+                } else {
+                    Some(self.parse_block()?)
+                }
+            }
+            _ => None,
+        };
+        Ok(StmtKind::If {
+            cond,
+            then,
+            otherwise,
+        })
+    }
+
+    // else branch made of a single nested if
+    fn parse_else_if(&mut self) -> Result<Block, ParseError> {
+        let pos = self.peek().pos.clone();
+        let kind = self.parse_if()?;
+        Ok(vec![Stmt { kind, pos, id: NodeId::default() }])
+    }
+
+    // block of a loop, where break and next are allowed
+    fn parse_loop_body(&mut self) -> Result<Block, ParseError> {
+        self.loop_depth += 1;
+        let body = self.parse_block();
+        self.loop_depth -= 1;
+        body
+    }
+
+    // for := 'for' ident '=' expr 'to' expr ('step' expr)? block
+    fn parse_for(&mut self) -> Result<StmtKind, ParseError> {
+        self.traced("parse_for", Self::parse_for_inner)
+    }
+
+    fn parse_for_inner(&mut self) -> Result<StmtKind, ParseError> {
+        self.expect(Token::For, "'for'")?;
+        let (var, _) = self.expect_ident("loop variable")?;
+        self.expect(Token::Equal, "'='")?;
+        let start = self.parse_expr()?;
+        self.expect(Token::To, "'to'")?;
+        let end = self.parse_expr()?;
+        let step = if self.check(&Token::Step) {
+            self.advance();
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        let body = self.parse_loop_body()?;
+        Ok(StmtKind::For {
+            var,
+            start,
+            end,
+            step,
+            body,
+        })
+    }
+
+    // args := '(' (arg (',' arg)*)? ')'
+    // arg := (ident ':')? expr, the named arguments come after the positional ones
+    fn parse_args(&mut self) -> Result<Vec<Arg>, ParseError> {
+        self.traced("parse_args", Self::parse_args_inner)
+    }
+
+    fn parse_args_inner(&mut self) -> Result<Vec<Arg>, ParseError> {
+        self.expect(Token::LParen, "'('")?;
+        let mut args: Vec<Arg> = Vec::new();
+        if !self.check(&Token::RParen) {
+            loop {
+                let named = matches!(self.peek().token, Token::Ident(_))
+                    && self.tokens.get(self.cur + 1).is_some_and(|t| t.token == Token::Colon);
+                let name = if named {
+                    let (name, _) = self.expect_ident("parameter name")?;
+                    self.advance(); // ':'
+                    Some(name)
+                } else if args.last().is_some_and(|arg| arg.name.is_some()) {
+                    return Err(ParseError::Syntax {
+                        message: "positional argument after a named argument".to_string(),
+                        pos: self.peek().pos.clone(),
+                    });
+                } else {
+                    None
+                };
+                let value = self.parse_expr()?;
+                args.push(Arg { name, value });
+                if self.check(&Token::Comma) {
+                    self.advance();
+                } else if self.check(&Token::RParen) {
+                    break;
+                } else {
+                    return Err(self.unexpected_one_of(&["','", "')'"]));
+                }
+            }
+        }
+        self.expect(Token::RParen, "')'")?;
+        Ok(args)
+    }
+
+    // expr := unary (binop unary)*, the operators bind by Token::infix_precedence
+    // and associate to the left
+    fn parse_expr(&mut self) -> Result<Expr, ParseError> {
+        if !self.trace {
+            return self.parse_binary_inner(1);
+        }
+        self.traced("parse_expr", |parser| parser.parse_binary(1))
+    }
+
+    // an expression whose operators bind at least as tightly as min_precedence
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expr, ParseError> {
+        if !self.trace {
+            return self.parse_binary_inner(min_precedence);
+        }
+        self.traced("parse_binary", |parser| parser.parse_binary_inner(min_precedence))
+    }
+
+    fn parse_binary_inner(&mut self, min_precedence: u8) -> Result<Expr, ParseError> {
+        let mut lhs = self.parse_unary()?;
+        while let Some(precedence) = self.peek().token.infix_precedence()
+            && precedence >= min_precedence
+            && let Some(op) = Self::binary_op(&self.peek().token)
+        {
+            lhs = self.parse_operand(lhs, op, precedence)?;
+        }
+        Ok(lhs)
+    }
+
+    // the operator and its right-hand side, which binds tighter than it
+    fn parse_operand(&mut self, lhs: Expr, op: BinaryOp, precedence: u8) -> Result<Expr, ParseError> {
+        let pos = self.advance().pos;
+        let rhs = self.parse_binary(precedence + 1)?;
+        Ok(Expr {
+            kind: ExprKind::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+            pos,
+            id: NodeId::default(),
+        })
+    }
+
+    fn binary_op(token: &Token) -> Option<BinaryOp> {
+        match token {
+            Token::OrOr => Some(BinaryOp::Or),
+            Token::AndAnd => Some(BinaryOp::And),
+            Token::EqEq => Some(BinaryOp::Eq),
+            Token::NotEq => Some(BinaryOp::NotEq),
+            Token::Lt => Some(BinaryOp::Lt),
+            Token::Le => Some(BinaryOp::Le),
+            Token::Gt => Some(BinaryOp::Gt),
+            Token::Ge => Some(BinaryOp::Ge),
+            Token::Plus => Some(BinaryOp::Add),
+            Token::Minus => Some(BinaryOp::Sub),
+            Token::Star => Some(BinaryOp::Mul),
+            Token::Slash => Some(BinaryOp::Div),
+            _ => None,
+        }
+    }
+
+    // every nested expression goes through here, so the depth is checked once per level
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        if !self.trace {
+            return self.parse_unary_inner();
+        }
+        self.traced("parse_unary", Self::parse_unary_inner)
+    }
+
+    // unary := '-' unary | cast
+    fn parse_unary_inner(&mut self) -> Result<Expr, ParseError> {
+        if self.depth >= self.max_depth {
+            return Err(self.too_deep());
+        }
+        self.depth += 1;
+        let expr = if self.check(&Token::Minus) { self.parse_negation() } else { self.parse_cast() };
+        self.depth -= 1;
+        expr
+    }
+
+    fn too_deep(&self) -> ParseError {
+        ParseError::Syntax {
+            message: "expression nesting too deep".to_string(),
+            pos: self.peek().pos.clone(),
+        }
+    }
+
+    fn parse_negation(&mut self) -> Result<Expr, ParseError> {
+        let pos = self.advance().pos;
+        let expr = self.parse_unary()?;
+        Ok(Expr {
+            kind: ExprKind::Unary {
+                op: UnaryOp::Neg,
+                expr: Box::new(expr),
+            },
+            pos,
+            id: NodeId::default(),
+        })
+    }
+
+    // cast := index ('as' type)*
+    fn parse_cast(&mut self) -> Result<Expr, ParseError> {
+        if !self.trace {
+            return self.parse_cast_inner();
+        }
+        self.traced("parse_cast", Self::parse_cast_inner)
+    }
+
+    fn parse_cast_inner(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_index()?;
+        while self.check(&Token::As) {
+            expr = self.parse_cast_type(expr)?;
+        }
+        Ok(expr)
+    }
+
+    // 'as' type after the cast expression
+    fn parse_cast_type(&mut self, expr: Expr) -> Result<Expr, ParseError> {
+        let pos = self.advance().pos;
+        let ty = self.parse_type()?;
+        Ok(Expr {
+            kind: ExprKind::Cast {
+                expr: Box::new(expr),
+                ty,
+            },
+            pos,
+            id: NodeId::default(),
+        })
+    }
+
+    // index := primary ('[' expr ']')*
+    fn parse_index(&mut self) -> Result<Expr, ParseError> {
+        if !self.trace {
+            return self.parse_index_inner();
+        }
+        self.traced("parse_index", Self::parse_index_inner)
+    }
+
+    fn parse_index_inner(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_primary()?;
+        while self.check(&Token::LBracket) {
+            expr = self.parse_subscript(expr)?;
+        }
+        Ok(expr)
+    }
+
+    // '[' expr ']' after the indexed expression
+    fn parse_subscript(&mut self, array: Expr) -> Result<Expr, ParseError> {
+        let pos = self.advance().pos;
+        let index = self.parse_expr()?;
+        self.expect(Token::RBracket, "']'")?;
+        Ok(Expr {
+            kind: ExprKind::Index {
+                array: Box::new(array),
+                index: Box::new(index),
+            },
+            pos,
+            id: NodeId::default(),
+        })
+    }
+
+    // array := '[' (expr (',' expr)*)? ']'
+    fn parse_array(&mut self) -> Result<Vec<Expr>, ParseError> {
+        if !self.trace {
+            return self.parse_array_inner();
+        }
+        self.traced("parse_array", Self::parse_array_inner)
+    }
+
+    fn parse_array_inner(&mut self) -> Result<Vec<Expr>, ParseError> {
+        self.expect(Token::LBracket, "'['")?;
+        let mut elems = Vec::new();
+        if !self.check(&Token::RBracket) {
+            loop {
+                elems.push(self.parse_expr()?);
+                if self.check(&Token::Comma) {
+                    self.advance();
+                } else if self.check(&Token::RBracket) {
+                    break;
+                } else {
+                    return Err(self.unexpected_one_of(&["','", "']'"]));
+                }
+            }
+        }
+        self.expect(Token::RBracket, "']'")?;
+        Ok(elems)
+    }
+
+    // builtin := ('min' | 'max' | 'abs') '(' (expr (',' expr)*)? ')'
+    fn parse_builtin(&mut self, builtin: Builtin) -> Result<Expr, ParseError> {
+        let pos = self.advance().pos;
+        self.expect(Token::LParen, "'('")?;
+        let mut args = Vec::new();
+        if !self.check(&Token::RParen) {
+            args.push(self.parse_expr()?);
+            while self.check(&Token::Comma) {
+                self.advance();
+                args.push(self.parse_expr()?);
+            }
+        }
+        if !self.check(&Token::RParen) {
+            return Err(self.unexpected_one_of(&["','", "')'"]));
+        }
+        self.advance();
+        if args.len() != builtin.arity() {
+            return Err(ParseError::Syntax {
+                message: format!("{} takes {} argument(s), {} given", builtin.name(), builtin.arity(), args.len()),
+                pos,
+            });
+        }
+        Ok(Expr {
+            kind: ExprKind::Builtin { name: builtin, args },
+            pos,
+            id: NodeId::default(),
+        })
+    }
+
+    // primary := literal | ident | 'nl' | array | builtin | ('to_str' | 'len') '(' expr ')'
+    //          | '(' expr (':' type)? ')'
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        if !self.trace {
+            return self.parse_primary_inner();
+        }
+        self.traced("parse_primary", Self::parse_primary_inner)
+    }
+
+    fn parse_primary_inner(&mut self) -> Result<Expr, ParseError> {
+        let builtin = match &self.peek().token {
+            Token::Ident(name) if self.tokens.get(self.cur + 1).is_some_and(|t| t.token == Token::LParen) => Builtin::from_name(name),
+            _ => None,
+        };
+        if let Some(builtin) = builtin {
+            return self.parse_builtin(builtin);
+        }
+        // the forms nesting an expression have their own function, the frames of a deep
+        // nesting stay small
+        match self.peek().token {
+            Token::LParen => self.parse_paren(),
+            Token::LBracket => self.parse_array_literal(),
+            Token::ToStr | Token::ToInt | Token::ToFloat | Token::Len => self.parse_conversion(),
+            _ => self.parse_literal(),
+        }
+    }
+
+    fn parse_paren(&mut self) -> Result<Expr, ParseError> {
+        self.advance();
+        let mut expr = self.parse_expr()?;
+        // the ascription colon only appears inside parentheses, so it can't be
+        // confused with the precision of a print
+        if self.check(&Token::Colon) {
+            expr = self.parse_ascription(expr)?;
+        }
+        self.expect(Token::RParen, "')'")?;
+        Ok(expr)
+    }
+
+    // ':' type after the ascribed expression
+    fn parse_ascription(&mut self, expr: Expr) -> Result<Expr, ParseError> {
+        let pos = self.advance().pos;
+        let ty = self.parse_type()?;
+        Ok(Expr {
+            kind: ExprKind::Ascription {
+                expr: Box::new(expr),
+                ty,
+            },
+            pos,
+            id: NodeId::default(),
+        })
+    }
+
+    fn parse_array_literal(&mut self) -> Result<Expr, ParseError> {
+        let pos = self.peek().pos.clone();
+        let elems = self.parse_array()?;
+        Ok(Expr {
+            kind: ExprKind::Array(elems),
+            pos,
+            id: NodeId::default(),
+        })
     }
 
-    fn parse_program(&mut self) -> Result<(), ParseError> {
-        
-        Ok(())
+    // ('to_str' | 'to_int' | 'to_float' | 'len') '(' expr ')'
+    fn parse_conversion(&mut self) -> Result<Expr, ParseError> {
+        let t = self.advance();
+        self.expect(Token::LParen, "'('")?;
+        let expr = Box::new(self.parse_expr()?);
+        self.expect(Token::RParen, "')'")?;
+        let kind = match t.token {
+            Token::ToStr => ExprKind::ToStr(expr),
+            Token::ToInt => ExprKind::ToInt(expr),
+            Token::ToFloat => ExprKind::ToFloat(expr),
+            _ => ExprKind::Len(expr),
+        };
+        Ok(Expr { kind, pos: t.pos, id: NodeId::default() })
     }
 
-    pub fn parse(&mut self, main_src_filename: String) -> Result<(), ParseError>{
+    // a literal or a variable, anything else is not an expression
+    fn parse_literal(&mut self) -> Result<Expr, ParseError> {
+        let t = self.peek().clone();
+        let kind = match t.token {
+            Token::Integer(n) => ExprKind::Integer(n),
+            Token::Float(x) => ExprKind::Float(x),
+            Token::Str(mut s) => {
+                // adjacent string literals are one string : "foo" "bar" is "foobar"
+                self.advance();
+                while let Token::Str(next) = &self.peek().token {
+                    s.push_str(next);
+                    self.advance();
+                }
+                return Ok(Expr {
+                    kind: ExprKind::Str(s),
+                    pos: t.pos,
+                    id: NodeId::default(),
+                });
+            }
+            Token::True => ExprKind::Bool(true),
+            Token::False => ExprKind::Bool(false),
+            Token::Nl => ExprKind::Str("\n".to_string()),
+            Token::Ident(name) => ExprKind::Var(name),
+            _ => return Err(self.misplaced_loop_keyword().unwrap_or_else(|| self.unexpected("expression"))),
+        };
+        self.advance();
+        Ok(Expr { kind, pos: t.pos, id: NodeId::default() })
+    }
+
+    pub fn parse(&mut self, main_src_filename: String) -> Result<Program, ParseError>{
         let mut lex = Lexer::new(main_src_filename);
-        self.tokens=lex.tokenize()?;
-        let ts = TokenStream { tokens: self.tokens.clone() };
-        println!("{}",ts);
-        self.parse_program()?;
-        Ok(())
+        lex.set_options(self.lexer_options.clone());
+        let ts = TokenStream { tokens: lex.tokenize()? };
+        self.warnings = lex.warnings().to_vec();
+        if self.show_tokens {
+            println!("{}",ts);
+        }
+        self.tokens = ts.filter_trivia().tokens;
+        self.timings = lex.timings().to_vec();
+        self.has_imports = !lex.imports().is_empty();
+        let mut program = self.parse_tokens()?;
+        program.imports = lex.imports().to_vec();
+        Ok(program)
+    }
+
+    // parser over an already lexed stream, the trivia is dropped
+    pub fn from_stream(stream: TokenStream) -> Self {
+        let mut tokens = stream.filter_trivia().tokens;
+        if tokens.last().is_none_or(|t| t.token != Token::Eof) {
+            let pos = tokens.last().map(|t| t.pos.clone()).unwrap_or_default();
+            tokens.push(LexToken { token: Token::Eof, pos });
+        }
+        Self { tokens, ..Self::new() }
+    }
+
+    // parse the tokens given to from_stream or lexed by parse, the imports are already spliced
+    pub fn parse_tokens(&mut self) -> Result<Program, ParseError> {
+        if self.verbosity >= 1 {
+            eprintln!("parsing {} tokens", self.tokens.len());
+        }
+        self.cur = 0;
+        self.depth = 0;
+        self.loop_depth = 0;
+        self.trace_depth = 0;
+        let start = Instant::now();
+        let program = self.parse_program()?;
+        self.timings.push(("parsing", start.elapsed()));
+        Ok(program)
+    }
+
+    // parse statements outside of any function, as typed in the REPL
+    pub fn parse_statements(&mut self, tokens: Vec<LexToken>) -> Result<Block, ParseError> {
+        self.tokens = tokens;
+        self.cur = 0;
+        self.depth = 0;
+        self.loop_depth = 0;
+        let mut block = self.parse_statements_until_brace()?;
+        if !self.check(&Token::Eof) {
+            return Err(self.unexpected("statement"));
+        }
+        ast::number_nodes(&mut block, &mut self.next_node);
+        Ok(block)
     }
 }
@@ -0,0 +1,294 @@
+use std::collections::{HashMap, HashSet};
+use std::{error, fmt};
+
+use crate::ast::{Block, Expr, ExprKind, Function, NodeId, NodeMap, Program, Stmt, StmtKind};
+use crate::lexer::Position;
+use crate::lint::Warning;
+
+#[derive(Debug)]
+pub struct ResolveError {
+    pub message: String,
+    pub pos: Position,
+}
+
+// Format how a resolve error is displayed
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Resolve error : [{}] at {} ({}:{})",
+            self.message, self.pos.file_name, self.pos.line, self.pos.col
+        )
+    }
+}
+
+impl error::Error for ResolveError {}
+
+// Declaration of a variable : the statement declaring it, none for a parameter which is
+// always assigned, and where it is for the warnings
+#[derive(Clone)]
+struct Decl {
+    stmt: Option<NodeId>,
+    pos: Position,
+}
+
+// Check that every name is declared before use. A block opens a scope, and a for loop
+// variable is a new binding visible in the loop body only, shadowing any outer variable.
+// A variable declared without a value must be assigned on every path before it is used.
+// A function declared in a block is visible from the rest of that block only.
+// A let is visible in its block, a local in the whole function once declared. Declaring the
+// same local again assigns it, so a local declared in the two branches of an if is assigned
+// after the if. A local declared in a loop body or in one branch only is still visible after
+// it, but reading it there is a possibly-uninitialized use.
+pub struct Resolver {
+    functions: HashSet<String>,
+    local_functions: Vec<HashSet<String>>, // functions declared in the enclosing blocks
+    scopes: Vec<HashMap<String, Decl>>, // declaration of the visible variables
+    assigned: NodeMap<()>, // declarations assigned on every path to the current statement
+    locals: NodeMap<()>, // declarations made by local
+    warn_shadow: bool, // warn when a let hides a variable of an enclosing block
+    warnings: Vec<Warning>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            functions: HashSet::new(),
+            local_functions: Vec::new(),
+            scopes: Vec::new(),
+            assigned: NodeMap::new(),
+            locals: NodeMap::new(),
+            warn_shadow: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    // warn when a let hides a variable of an enclosing block
+    pub fn set_warn_shadow(&mut self, warn_shadow: bool) {
+        self.warn_shadow = warn_shadow;
+    }
+
+    // warnings of the last resolve
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub fn resolve(&mut self, program: &Program) -> Result<(), ResolveError> {
+        self.functions = program.functions().map(|f| f.name.clone()).collect();
+        self.warnings.clear();
+        self.locals = NodeMap::new();
+        for function in program.functions() {
+            self.resolve_function(function)?;
+        }
+        Ok(())
+    }
+
+    // statements of a REPL line, the names they declare stay visible for the next lines.
+    // Nothing is declared by a line with an error
+    pub fn resolve_statements(&mut self, block: &Block) -> Result<(), ResolveError> {
+        if self.scopes.is_empty() {
+            self.scopes.push(HashMap::new());
+            self.local_functions.push(HashSet::new());
+        }
+        let saved = (self.scopes.clone(), self.local_functions.clone(), self.assigned.clone());
+        let result = block.iter().try_for_each(|stmt| self.resolve_stmt(stmt));
+        if result.is_err() {
+            (self.scopes, self.local_functions, self.assigned) = saved;
+        }
+        result
+    }
+
+    // the body of a function only sees its parameters, the caller keeps its variables
+    fn resolve_function(&mut self, function: &Function) -> Result<(), ResolveError> {
+        let caller = std::mem::replace(&mut self.scopes, vec![HashMap::new()]);
+        let caller_assigned = std::mem::take(&mut self.assigned);
+        let mut result = Ok(());
+        for param in &function.params {
+            if self.scopes[0].contains_key(&param.name) {
+                result = Err(Self::error(format!("parameter {} already defined", param.name), &param.pos));
+                break;
+            }
+            self.declare(&param.name, None, &param.pos);
+        }
+        if result.is_ok() {
+            result = self.resolve_block(&function.body);
+        }
+        self.scopes = caller;
+        self.assigned = caller_assigned;
+        result
+    }
+
+    fn error(message: String, pos: &Position) -> ResolveError {
+        ResolveError {
+            message,
+            pos: pos.clone(),
+        }
+    }
+
+    fn declare(&mut self, name: &str, stmt: Option<NodeId>, pos: &Position) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), Decl { stmt, pos: pos.clone() });
+        }
+    }
+
+    fn lookup(&self, name: &str, pos: &Position) -> Result<&Decl, ResolveError> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .ok_or_else(|| Self::error(format!("unknown variable {}", name), pos))
+    }
+
+    fn resolve_block(&mut self, block: &Block) -> Result<(), ResolveError> {
+        self.scopes.push(HashMap::new());
+        self.local_functions.push(HashSet::new());
+        let result = block.iter().try_for_each(|stmt| self.resolve_stmt(stmt));
+        self.local_functions.pop();
+        self.scopes.pop();
+        result
+    }
+
+    // a loop body may not run, what it assigns is still unassigned after the loop
+    fn resolve_loop_body(&mut self, body: &Block) -> Result<(), ResolveError> {
+        let before = self.assigned.clone();
+        let result = self.resolve_block(body);
+        self.assigned = before;
+        result
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) -> Result<(), ResolveError> {
+        match &stmt.kind {
+            StmtKind::Let { name, value, local: true, .. } => {
+                if let Some(value) = value {
+                    self.resolve_expr(value)?;
+                }
+                // the function scope is the first one, with the parameters
+                let decl = self.scopes[0].entry(name.clone()).or_insert_with(|| Decl { stmt: Some(stmt.id), pos: stmt.pos.clone() });
+                // a parameter declared again as a local stays a parameter, always assigned
+                if let Some(id) = decl.stmt {
+                    self.locals.insert(id, ());
+                    if value.is_some() {
+                        self.assigned.insert(id, ());
+                    }
+                }
+            }
+            StmtKind::Let { name, value, .. } => {
+                if let Some(value) = value {
+                    self.resolve_expr(value)?;
+                    self.assigned.insert(stmt.id, ());
+                }
+                if self.warn_shadow
+                    && let Some((_, outer)) = self.scopes.split_last()
+                    && let Some(shadowed) = outer.iter().rev().find_map(|scope| scope.get(name))
+                {
+                    self.warnings.push(Warning {
+                        message: format!(
+                            "variable {} shadows the one declared at {} ({}:{})",
+                            name, shadowed.pos.file_name, shadowed.pos.line, shadowed.pos.col
+                        ),
+                        pos: stmt.pos.clone(),
+                    });
+                }
+                self.declare(name, Some(stmt.id), &stmt.pos);
+            }
+            StmtKind::Assign { name, value } => {
+                let decl = self.lookup(name, &stmt.pos)?.stmt;
+                self.resolve_expr(value)?;
+                if let Some(id) = decl {
+                    self.assigned.insert(id, ());
+                }
+            }
+            StmtKind::Print { args, .. } => args.iter().try_for_each(|arg| self.resolve_expr(arg))?,
+            StmtKind::Call { name, args } => {
+                if !self.functions.contains(name) && !self.local_functions.iter().any(|scope| scope.contains(name)) {
+                    return Err(Self::error(format!("unknown function {}", name), &stmt.pos));
+                }
+                args.iter().try_for_each(|arg| self.resolve_expr(&arg.value))?;
+            }
+            StmtKind::If { cond, then, otherwise } => {
+                self.resolve_expr(cond)?;
+                // a variable is assigned after the if when both branches assign it
+                let before = self.assigned.clone();
+                self.resolve_block(then)?;
+                let after_then = std::mem::replace(&mut self.assigned, before);
+                if let Some(otherwise) = otherwise {
+                    self.resolve_block(otherwise)?;
+                }
+                self.assigned.retain(|decl, _| after_then.contains(decl));
+            }
+            StmtKind::For { var, start, end, step, body } => {
+                // the bounds are evaluated before the loop variable exists
+                self.resolve_expr(start)?;
+                self.resolve_expr(end)?;
+                if let Some(step) = step {
+                    self.resolve_expr(step)?;
+                }
+                self.scopes.push(HashMap::from([(var.clone(), Decl { stmt: Some(stmt.id), pos: stmt.pos.clone() })]));
+                self.assigned.insert(stmt.id, ());
+                let result = self.resolve_loop_body(body);
+                self.scopes.pop();
+                result?;
+            }
+            StmtKind::While { cond, body } => {
+                self.resolve_expr(cond)?;
+                self.resolve_loop_body(body)?;
+            }
+            StmtKind::Break(Some(value)) => self.resolve_expr(value)?,
+            StmtKind::Break(None) | StmtKind::Next => {}
+            StmtKind::Fn(function) => {
+                if let Some(scope) = self.local_functions.last_mut()
+                    && !scope.insert(function.name.clone())
+                {
+                    return Err(Self::error(format!("function {} already defined", function.name), &function.pos));
+                }
+                // declared before its body so it can call itself
+                self.resolve_function(function)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) -> Result<(), ResolveError> {
+        match &expr.kind {
+            ExprKind::Integer(_) | ExprKind::Float(_) | ExprKind::Str(_) | ExprKind::Bool(_) => Ok(()),
+            ExprKind::Var(name) => {
+                // a parameter is always assigned
+                let Some(decl) = self.lookup(name, &expr.pos)?.stmt else {
+                    return Ok(());
+                };
+                if self.locals.contains(decl) && !self.assigned.contains(decl) {
+                    return Err(Self::error(
+                        format!("use of possibly-uninitialized local {}, not declared on every path to here", name),
+                        &expr.pos,
+                    ));
+                }
+                if !self.assigned.contains(decl) {
+                    return Err(Self::error(format!("use of possibly-uninitialized variable {}", name), &expr.pos));
+                }
+                Ok(())
+            }
+            ExprKind::Unary { expr, .. }
+            | ExprKind::ToStr(expr)
+            | ExprKind::ToInt(expr)
+            | ExprKind::ToFloat(expr)
+            | ExprKind::Len(expr)
+            | ExprKind::Cast { expr, .. }
+            | ExprKind::Ascription { expr, .. } => self.resolve_expr(expr),
+            ExprKind::Binary { lhs, rhs, .. }
+            | ExprKind::Index {
+                array: lhs,
+                index: rhs,
+            } => {
+                self.resolve_expr(lhs)?;
+                self.resolve_expr(rhs)
+            }
+            ExprKind::Array(elems) | ExprKind::Builtin { args: elems, .. } => elems.iter().try_for_each(|elem| self.resolve_expr(elem)),
+        }
+    }
+}
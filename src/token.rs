@@ -1,8 +1,14 @@
-use strum_macros::EnumString;
-#[derive(Clone,Debug, PartialEq, EnumString)]
+use std::fmt;
+
+use strum_macros::{AsRefStr, EnumDiscriminants, EnumString};
+// TokenKind is the same list of variants without their payload
+#[derive(Clone,Debug, PartialEq, EnumString, AsRefStr, EnumDiscriminants)]
+#[strum_discriminants(name(TokenKind), derive(Hash))]
 pub enum Token {
     #[strum(serialize = "import")]
     Import,
+    #[strum(serialize = "from")]
+    From,
     #[strum(serialize = "fn")]
     Fn,
     #[strum(serialize = "main")]
@@ -11,18 +17,30 @@ pub enum Token {
     Print,
     #[strum(serialize = "println")]
     Println,
+    #[strum(serialize = "eprint")]
+    Eprint,
+    #[strum(serialize = "eprintln")]
+    Eprintln,
     #[strum(serialize = "call")]
     Call,
-    #[strum(serialize = "_ident")]
+    #[strum(disabled)]
     Ident(String),
-    #[strum(serialize = "_str")]
+    #[strum(disabled)]
     Str(String),
-    #[strum(serialize = "_integer")]
+    #[strum(disabled)]
     Integer(i32),
-    #[strum(serialize = "_float")]
+    #[strum(disabled)]
     Float(f64),
     #[strum(serialize = "to_str")]
     ToStr,
+    #[strum(serialize = "to_int")]
+    ToInt,
+    #[strum(serialize = "to_float")]
+    ToFloat,
+    #[strum(serialize = "len")]
+    Len,
+    #[strum(serialize = "as")]
+    As,
     #[strum(serialize = "[")]
     LBracket,
     #[strum(serialize = "]")]
@@ -59,14 +77,38 @@ pub enum Token {
     False,
     #[strum(serialize = "=")]
     Equal,
+    #[strum(serialize = "==")]
+    EqEq,
+    #[strum(serialize = "!=")]
+    NotEq,
+    #[strum(serialize = "&&")]
+    AndAnd,
+    #[strum(serialize = "||")]
+    OrOr,
+    #[strum(serialize = "<")]
+    Lt,
+    #[strum(serialize = "<=")]
+    Le,
+    #[strum(serialize = ">")]
+    Gt,
+    #[strum(serialize = ">=")]
+    Ge,
     #[strum(serialize = "int")]
     IntType,
     #[strum(serialize = "float")]
     FloatType,
     #[strum(serialize = "let")]
     Let,
+    #[strum(serialize = "if")]
+    If,
+    #[strum(serialize = "elif")]
+    Elif,
+    #[strum(serialize = "else")]
+    Else,
     #[strum(serialize = "for")]
     For,
+    #[strum(serialize = "while")]
+    While,
     #[strum(serialize = "to")]
     To,
     #[strum(serialize = "step")]
@@ -75,6 +117,127 @@ pub enum Token {
     Next,
     #[strum(serialize = "break")]
     Break,
-    #[strum(serialize = "_eof")]
+    #[strum(disabled)]
+    Comment(String), // only produced when the lexer keeps the trivia
+    #[strum(disabled)]
+    ImportStart(String), // before the tokens of an imported file, with the import markers
+    #[strum(disabled)]
+    ImportEnd(String), // after the tokens of an imported file, with the import markers
+    #[strum(disabled)]
+    Underscore, // '_' alone, a word but not an identifier
+    #[strum(disabled)]
     Eof,
 }
+
+// Format a token as written in the source, an integral float keeps its decimal point
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "{}", name),
+            Token::Str(s) => write!(f, "{:?}", s),
+            Token::Integer(n) => write!(f, "{}", n),
+            Token::Float(x) if x.fract() == 0.0 && x.is_finite() => write!(f, "{:.1}", x),
+            Token::Float(x) => write!(f, "{}", x),
+            Token::Comment(text) => write!(f, "{}", text),
+            Token::ImportStart(file_name) => write!(f, "start of import {}", file_name),
+            Token::ImportEnd(file_name) => write!(f, "end of import {}", file_name),
+            Token::Underscore => write!(f, "_"),
+            Token::Eof => write!(f, "end of file"),
+            _ => write!(f, "{}", self.as_ref()),
+        }
+    }
+}
+
+// Token classification, for tooling
+impl Token {
+    // tokens without meaning for the grammar
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Token::Comment(_) | Token::ImportStart(_) | Token::ImportEnd(_))
+    }
+
+    pub fn kind(&self) -> TokenKind {
+        TokenKind::from(self)
+    }
+
+    // same variant whatever the payload, Ident("a") and Ident("b") are the same kind
+    pub fn same_kind(&self, other: &Token) -> bool {
+        self.kind() == other.kind()
+    }
+
+    // name of the variant without its payload, e.g. Ident for Ident("x")
+    pub fn kind_name(&self) -> String {
+        format!("{:?}", self.kind())
+    }
+
+    // binding power of a binary operator, higher binds tighter
+    pub fn infix_precedence(&self) -> Option<u8> {
+        match self {
+            Token::OrOr => Some(1),
+            Token::AndAnd => Some(2),
+            Token::EqEq | Token::NotEq | Token::Lt | Token::Le | Token::Gt | Token::Ge => Some(3),
+            Token::Plus | Token::Minus => Some(4),
+            Token::Star | Token::Slash => Some(5),
+            _ => None,
+        }
+    }
+
+    pub fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            Token::Import
+                | Token::From
+                | Token::Fn
+                | Token::Main
+                | Token::Print
+                | Token::Println
+                | Token::Eprint
+                | Token::Eprintln
+                | Token::Call
+                | Token::ToStr
+                | Token::ToInt
+                | Token::ToFloat
+                | Token::Len
+                | Token::As
+                | Token::Nl
+                | Token::Local
+                | Token::IntType
+                | Token::FloatType
+                | Token::Let
+                | Token::If
+                | Token::Elif
+                | Token::Else
+                | Token::For
+                | Token::While
+                | Token::To
+                | Token::Step
+                | Token::Next
+                | Token::Break
+        )
+    }
+
+    pub fn is_operator(&self) -> bool {
+        matches!(
+            self,
+            Token::Plus
+                | Token::Minus
+                | Token::Star
+                | Token::Slash
+                | Token::Equal
+                | Token::EqEq
+                | Token::NotEq
+                | Token::AndAnd
+                | Token::OrOr
+                | Token::Lt
+                | Token::Le
+                | Token::Gt
+                | Token::Ge
+        )
+    }
+
+    pub fn is_literal(&self) -> bool {
+        matches!(
+            self,
+            Token::Str(_) | Token::Integer(_) | Token::Float(_) | Token::True | Token::False
+        )
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::{error, fmt};
+
+use crate::ast::{BinaryOp, Block, Expr, ExprKind, Function, NodeId, NodeMap, Program, Stmt, StmtKind, Type, UnaryOp};
+use crate::lexer::Position;
+
+#[derive(Debug)]
+pub struct TypeError {
+    pub message: String,
+    pub pos: Position,
+}
+
+// Format how a type error is displayed
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Type error : [{}] at {} ({}:{})",
+            self.message, self.pos.file_name, self.pos.line, self.pos.col
+        )
+    }
+}
+
+impl error::Error for TypeError {}
+
+// Check the types of a whole program. By default an int is promoted to a float when
+// mixed with one, in strict mode the promotion is a type error, and so are the rows of
+// different lengths in a matrix literal.
+pub struct TypeChecker<'a> {
+    functions: HashMap<&'a str, &'a Function>,
+    local_functions: Vec<HashMap<String, Rc<Function>>>, // functions declared in the enclosing blocks
+    scopes: Vec<HashMap<String, Type>>, // variables visible from the current block
+    breaks: Vec<Option<Type>>, // type of the break values of each enclosing loop, once one is seen
+    types: NodeMap<Type>, // type of every expression checked
+    strict: bool,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(strict: bool) -> Self {
+        Self {
+            functions: HashMap::new(),
+            local_functions: Vec::new(),
+            scopes: Vec::new(),
+            breaks: Vec::new(),
+            types: NodeMap::new(),
+            strict,
+        }
+    }
+
+    pub fn check(&mut self, program: &'a Program) -> Result<(), TypeError> {
+        self.functions = program.functions().map(|f| (f.name.as_str(), f)).collect();
+        for function in program.functions() {
+            self.check_function(function)?;
+        }
+        Ok(())
+    }
+
+    // type of an expression once checked
+    pub fn type_of(&self, id: NodeId) -> Option<&Type> {
+        self.types.get(id)
+    }
+
+    // statements of a REPL line, the names they declare stay visible for the next lines.
+    // Nothing is declared by a line with an error
+    pub fn check_statements(&mut self, block: &Block) -> Result<(), TypeError> {
+        if self.scopes.is_empty() {
+            self.scopes.push(HashMap::new());
+            self.local_functions.push(HashMap::new());
+        }
+        let saved = (self.scopes.clone(), self.local_functions.clone());
+        let result = block.iter().try_for_each(|stmt| self.check_stmt(stmt));
+        if result.is_err() {
+            (self.scopes, self.local_functions) = saved;
+        }
+        result
+    }
+
+    // the body of a function only sees its parameters, and isn't in the loops of the caller
+    fn check_function(&mut self, function: &Function) -> Result<(), TypeError> {
+        let params = function.params.iter().map(|p| (p.name.clone(), p.ty.clone())).collect();
+        let caller = std::mem::replace(&mut self.scopes, vec![params]);
+        let caller_breaks = std::mem::take(&mut self.breaks);
+        let result = self.check_block(&function.body);
+        self.scopes = caller;
+        self.breaks = caller_breaks;
+        result
+    }
+
+    fn error(message: String, pos: &Position) -> TypeError {
+        TypeError {
+            message,
+            pos: pos.clone(),
+        }
+    }
+
+    fn declare(&mut self, name: &str, ty: Type) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), ty);
+        }
+    }
+
+    fn lookup(&self, name: &str, pos: &Position) -> Result<Type, TypeError> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+            .ok_or_else(|| Self::error(format!("unknown variable {}", name), pos))
+    }
+
+    // check that a value of type `found` can be stored where `expected` is required,
+    // the elements of an array are never promoted
+    fn coerce(&self, found: &Type, expected: &Type, pos: &Position) -> Result<(), TypeError> {
+        if found == expected || (!self.strict && *found == Type::Int && *expected == Type::Float) {
+            Ok(())
+        } else {
+            Err(Self::error(format!("expected {}, found {}", expected, found), pos))
+        }
+    }
+
+    // check an expression stored where `expected` is required, an empty array takes its type
+    // from there
+    fn check_value(&mut self, expr: &Expr, expected: &Type) -> Result<(), TypeError> {
+        if let (ExprKind::Array(elems), Type::Array(_)) = (&expr.kind, expected)
+            && elems.is_empty()
+        {
+            self.types.insert(expr.id, expected.clone());
+            return Ok(());
+        }
+        let found = self.check_expr(expr)?;
+        self.coerce(&found, expected, &expr.pos)
+    }
+
+    // if and while conditions, a number is never taken as a truth value
+    fn check_condition(&mut self, cond: &Expr) -> Result<(), TypeError> {
+        let ty = self.check_expr(cond)?;
+        if ty != Type::Bool {
+            return Err(Self::error(format!("condition must be a bool, found {}", ty), &cond.pos));
+        }
+        Ok(())
+    }
+
+    fn check_block(&mut self, block: &Block) -> Result<(), TypeError> {
+        self.scopes.push(HashMap::new());
+        self.local_functions.push(HashMap::new());
+        let result = block.iter().try_for_each(|stmt| self.check_stmt(stmt));
+        self.local_functions.pop();
+        self.scopes.pop();
+        result
+    }
+
+    fn check_loop_body(&mut self, body: &Block) -> Result<(), TypeError> {
+        self.breaks.push(None);
+        let result = self.check_block(body);
+        self.breaks.pop();
+        result
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt) -> Result<(), TypeError> {
+        match &stmt.kind {
+            StmtKind::Let { name, ty, value, local } => {
+                // declaring a local of the function again assigns it
+                if *local && let Some(expected) = self.scopes[0].get(name).cloned() {
+                    if let Some(ty) = ty
+                        && *ty != expected
+                    {
+                        return Err(Self::error(format!("local {} is already declared as {}", name, expected), &stmt.pos));
+                    }
+                    if let Some(value) = value {
+                        self.check_value(value, &expected)?;
+                    }
+                    return Ok(());
+                }
+                let ty = match (ty, value) {
+                    (Some(ty), Some(value)) => {
+                        self.check_value(value, ty)?;
+                        ty.clone()
+                    }
+                    (Some(ty), None) => ty.clone(),
+                    (None, Some(value)) => self.check_expr(value)?,
+                    (None, None) => {
+                        return Err(Self::error(format!("variable {} declared without a value needs a type", name), &stmt.pos));
+                    }
+                };
+                if *local {
+                    self.scopes[0].insert(name.clone(), ty);
+                } else {
+                    self.declare(name, ty);
+                }
+            }
+            StmtKind::Assign { name, value } => {
+                let expected = self.lookup(name, &stmt.pos)?;
+                self.check_value(value, &expected)?;
+            }
+            StmtKind::Print { args, precision, .. } => {
+                for arg in args {
+                    let ty = self.check_expr(arg)?;
+                    if precision.is_some() && ty != Type::Int && ty != Type::Float {
+                        return Err(Self::error(format!("format precision needs a number, found {}", ty), &arg.pos));
+                    }
+                }
+            }
+            StmtKind::Call { name, args } => {
+                let local = self.local_functions.iter().rev().find_map(|scope| scope.get(name.as_str())).cloned();
+                let function: &Function = match &local {
+                    Some(function) => function,
+                    None => self
+                        .functions
+                        .get(name.as_str())
+                        .copied()
+                        .ok_or_else(|| Self::error(format!("unknown function {}", name), &stmt.pos))?,
+                };
+                if args.len() != function.param_count() {
+                    return Err(Self::error(
+                        format!("function {} takes {} argument(s), {} given", name, function.param_count(), args.len()),
+                        &stmt.pos,
+                    ));
+                }
+                let order = function.arg_order(args).map_err(|message| Self::error(message, &stmt.pos))?;
+                for (arg, index) in args.iter().zip(order) {
+                    self.check_value(&arg.value, &function.params[index].ty)?;
+                }
+            }
+            StmtKind::If { cond, then, otherwise } => {
+                self.check_condition(cond)?;
+                self.check_block(then)?;
+                if let Some(otherwise) = otherwise {
+                    self.check_block(otherwise)?;
+                }
+            }
+            StmtKind::For { var, start, end, step, body } => {
+                let mut ty = self.check_expr(start)?;
+                for bound in [Some(end), step.as_ref()].into_iter().flatten() {
+                    let found = self.check_expr(bound)?;
+                    ty = self.arithmetic(&ty, &found, &bound.pos)?;
+                }
+                if ty == Type::Str {
+                    return Err(Self::error("loop bounds must be numbers".to_string(), &start.pos));
+                }
+                self.scopes.push(HashMap::from([(var.clone(), ty)]));
+                let result = self.check_loop_body(body);
+                self.scopes.pop();
+                result?;
+            }
+            StmtKind::While { cond, body } => {
+                self.check_condition(cond)?;
+                self.check_loop_body(body)?;
+            }
+            StmtKind::Break(Some(value)) => {
+                let found = self.check_expr(value)?;
+                match self.breaks.last_mut() {
+                    Some(Some(ty)) if *ty != found => {
+                        return Err(Self::error(
+                            format!("break values of a loop must have the same type, found {} and {}", ty, found),
+                            &value.pos,
+                        ));
+                    }
+                    Some(ty) => *ty = Some(found),
+                    None => {}
+                }
+            }
+            StmtKind::Break(None) | StmtKind::Next => {}
+            StmtKind::Fn(function) => {
+                if let Some(scope) = self.local_functions.last_mut() {
+                    scope.insert(function.name.clone(), function.clone());
+                }
+                self.check_function(function)?;
+            }
+        }
+        Ok(())
+    }
+
+    // type of an arithmetic operation between two operands
+    fn arithmetic(&self, lhs: &Type, rhs: &Type, pos: &Position) -> Result<Type, TypeError> {
+        match (lhs, rhs) {
+            (Type::Int, Type::Int) => Ok(Type::Int),
+            (Type::Float, Type::Float) => Ok(Type::Float),
+            (Type::Int, Type::Float) | (Type::Float, Type::Int) if !self.strict => Ok(Type::Float),
+            (Type::Int, Type::Float) | (Type::Float, Type::Int) => Err(Self::error(
+                "mixed int and float arithmetic, convert one operand explicitly".to_string(),
+                pos,
+            )),
+            _ => Err(Self::error(format!("can't use {} and {} in an arithmetic operation", lhs, rhs), pos)),
+        }
+    }
+
+    fn check_expr(&mut self, expr: &Expr) -> Result<Type, TypeError> {
+        let ty = self.check_expr_kind(expr)?;
+        self.types.insert(expr.id, ty.clone());
+        Ok(ty)
+    }
+
+    fn check_expr_kind(&mut self, expr: &Expr) -> Result<Type, TypeError> {
+        match &expr.kind {
+            ExprKind::Integer(_) => Ok(Type::Int),
+            ExprKind::Float(_) => Ok(Type::Float),
+            ExprKind::Str(_) => Ok(Type::Str),
+            ExprKind::Bool(_) => Ok(Type::Bool),
+            ExprKind::Var(name) => self.lookup(name, &expr.pos),
+            ExprKind::Unary { op: UnaryOp::Neg, expr: operand } => match self.check_expr(operand)? {
+                ty @ (Type::Int | Type::Float) => Ok(ty),
+                ty => Err(Self::error(format!("can't negate a {}", ty), &expr.pos)),
+            },
+            ExprKind::Binary { op, lhs, rhs } => {
+                let lhs = self.check_expr(lhs)?;
+                let rhs = self.check_expr(rhs)?;
+                match op {
+                    BinaryOp::Add if lhs == Type::Str && rhs == Type::Str => Ok(Type::Str), // string concatenation
+                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => self.arithmetic(&lhs, &rhs, &expr.pos),
+                    BinaryOp::And | BinaryOp::Or if lhs == Type::Bool && rhs == Type::Bool => Ok(Type::Bool),
+                    BinaryOp::And | BinaryOp::Or => Err(Self::error(
+                        format!("can't use {} and {} in a logical operation", lhs, rhs),
+                        &expr.pos,
+                    )),
+                    BinaryOp::Eq | BinaryOp::NotEq if lhs == rhs => Ok(Type::Bool),
+                    _ => {
+                        self.arithmetic(&lhs, &rhs, &expr.pos)?;
+                        Ok(Type::Bool)
+                    }
+                }
+            }
+            ExprKind::ToStr(operand) => {
+                self.check_expr(operand)?;
+                Ok(Type::Str)
+            }
+            ExprKind::ToInt(operand) | ExprKind::ToFloat(operand) => {
+                let (name, ty) = match expr.kind {
+                    ExprKind::ToInt(_) => ("to_int", Type::Int),
+                    _ => ("to_float", Type::Float),
+                };
+                let found = self.check_expr(operand)?;
+                if found != Type::Str {
+                    return Err(Self::error(format!("{} expects a string, found {}", name, found), &operand.pos));
+                }
+                Ok(ty)
+            }
+            ExprKind::Array(elems) => {
+                let Some(first) = elems.first() else {
+                    return Err(Self::error("empty array needs a type annotation".to_string(), &expr.pos));
+                };
+                let mut ty = self.check_expr(first)?;
+                for elem in &elems[1..] {
+                    let found = self.check_expr(elem)?;
+                    ty = match (&ty, &found) {
+                        _ if ty == found => ty,
+                        (Type::Int, Type::Float) | (Type::Float, Type::Int) => self.arithmetic(&ty, &found, &elem.pos)?,
+                        _ => return Err(Self::error(format!("array elements must have the same type, found {} and {}", ty, found), &elem.pos)),
+                    };
+                }
+                // in strict mode the rows of a matrix literal have the same length
+                if self.strict
+                    && let ExprKind::Array(first_row) = &first.kind
+                {
+                    for row in &elems[1..] {
+                        if let ExprKind::Array(other) = &row.kind
+                            && other.len() != first_row.len()
+                        {
+                            return Err(Self::error(
+                                format!("ragged array, rows of {} and {} elements", first_row.len(), other.len()),
+                                &row.pos,
+                            ));
+                        }
+                    }
+                }
+                Ok(Type::Array(Box::new(ty)))
+            }
+            ExprKind::Index { array, index } => {
+                let ty = self.check_expr(array)?;
+                let Type::Array(elem) = ty else {
+                    return Err(Self::error(format!("can't index a {}", ty), &expr.pos));
+                };
+                let found = self.check_expr(index)?;
+                if found != Type::Int {
+                    return Err(Self::error(format!("array index must be an int, found {}", found), &index.pos));
+                }
+                Ok(*elem)
+            }
+            ExprKind::Len(operand) => match self.check_expr(operand)? {
+                Type::Array(_) | Type::Str => Ok(Type::Int),
+                ty => Err(Self::error(format!("can't take the length of a {}", ty), &expr.pos)),
+            },
+            ExprKind::Builtin { name, args } => {
+                let mut types = Vec::new();
+                for arg in args {
+                    let ty = self.check_expr(arg)?;
+                    if ty != Type::Int && ty != Type::Float {
+                        return Err(Self::error(format!("{} expects numbers, found {}", name.name(), ty), &arg.pos));
+                    }
+                    types.push(ty);
+                }
+                // mixed int and float arguments give a float, as in arithmetic
+                let Some((first, others)) = types.split_first() else {
+                    return Err(Self::error(format!("{} needs an argument", name.name()), &expr.pos));
+                };
+                others.iter().try_fold(first.clone(), |ty, other| self.arithmetic(&ty, other, &expr.pos))
+            }
+            ExprKind::Cast { expr: operand, ty } => {
+                let found = self.check_expr(operand)?;
+                let numeric = |ty: &Type| *ty == Type::Int || *ty == Type::Float;
+                if !numeric(&found) || !numeric(ty) {
+                    return Err(Self::error(format!("can't cast {} to {}", found, ty), &expr.pos));
+                }
+                Ok(ty.clone())
+            }
+            ExprKind::Ascription { expr: operand, ty } => {
+                self.check_value(operand, ty)?;
+                Ok(ty.clone())
+            }
+        }
+    }
+}
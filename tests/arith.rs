@@ -0,0 +1,96 @@
+use mpl2::{MplError, parse_str, run_str};
+
+fn run(body: &str) -> Result<String, MplError> {
+    run_str(&format!("fn main() {{\n{}\n}}\n", body), "arith.mpl")
+}
+
+// message, line and column of the runtime error of a main with the given body
+fn runtime_error(body: &str) -> (String, usize, usize) {
+    match run(body) {
+        Err(MplError::Runtime(e)) => (e.message, e.pos.line, e.pos.col),
+        other => panic!("{}: {:?}", body, other),
+    }
+}
+
+// the ints are 32 bits, going past is an error at the operator instead of a wrap
+#[test]
+fn int_overflow() {
+    let overflow = |line, col| ("arithmetic overflow".to_string(), line, col);
+    assert_eq!(runtime_error("    println 2147483647 + 1"), overflow(2, 24));
+    assert_eq!(runtime_error("    let x = 2147483647\n    let y = x + 1"), overflow(3, 15));
+    assert_eq!(runtime_error("    let x = -2147483647\n    println x - 2"), overflow(3, 15));
+    assert_eq!(runtime_error("    let x = 65536\n    println x * x"), overflow(3, 15));
+    assert_eq!(runtime_error("    let x = -2147483647 - 1\n    println -x"), overflow(3, 13));
+    assert_eq!(runtime_error("    let x = -2147483647 - 1\n    println x / -1"), overflow(3, 15));
+    assert_eq!(run("    let x = 2147483646\n    println x + 1, -2147483647 - 1").unwrap(), "2147483647 -2147483648\n");
+}
+
+// || binds looser than &&, which binds looser than the comparisons, themselves looser than
+// the arithmetic
+#[test]
+fn logical_precedence() {
+    for (expr, sexpr, value) in [
+        ("1 < 2 && 3 > 2 || 1 == 2", "(|| (&& (< 1 2) (> 3 2)) (== 1 2))", "true"),
+        ("1 + 1 == 2 && true", "(&& (== (+ 1 1) 2) true)", "true"),
+        ("true || false && false", "(|| true (&& false false))", "true"),
+        ("(true || false) && false", "(&& (|| true false) false)", "false"),
+        ("1 < 2 == true", "(== (< 1 2) true)", "true"),
+        ("false && true || 2 * 3 >= 6", "(|| (&& false true) (>= (* 2 3) 6))", "true"),
+    ] {
+        let src = format!("fn main() {{\n    println {}\n}}\n", expr);
+        assert_eq!(parse_str(&src, "arith.mpl").unwrap().to_sexpr(), format!("(fn main () (println {}))", sexpr));
+        assert_eq!(run_str(&src, "arith.mpl").unwrap(), format!("{}\n", value));
+    }
+}
+
+// strings parsed as numbers, the spaces around are ignored
+#[test]
+fn string_to_number() {
+    let body = "    println to_int(\"42\"), to_int(\" -7 \"), to_float(\"2.5\"), to_float(\"3\"), to_float(\"1e2\")\n    println to_int(\"1\") + 1";
+    assert_eq!(run(body).unwrap(), "42 -7 2.5 3.0 100.0\n2\n");
+}
+
+#[test]
+fn invalid_string_to_number() {
+    assert_eq!(runtime_error("    println to_int(\"4x\")"), ("cannot parse '4x' as int".to_string(), 2, 13));
+    assert_eq!(runtime_error("    println to_int(\"2.5\")"), ("cannot parse '2.5' as int".to_string(), 2, 13));
+    assert_eq!(runtime_error("    println to_int(\"\")"), ("cannot parse '' as int".to_string(), 2, 13));
+    assert_eq!(runtime_error("    println to_float(\"abc\")"), ("cannot parse 'abc' as float".to_string(), 2, 13));
+    for (body, message) in [
+        ("    println to_int(2.9)", "to_int expects a string, found float"),
+        ("    println to_float(true)", "to_float expects a string, found bool"),
+    ] {
+        match run(body) {
+            Err(MplError::Type(e)) => assert_eq!(e.message, message),
+            other => panic!("{}: {:?}", body, other),
+        }
+    }
+}
+
+// the right-hand side isn't evaluated when the left-hand side decides, its runtime error
+// doesn't happen
+#[test]
+fn short_circuit() {
+    assert_eq!(run("    println false && [1][5] == 1, true || 1 / 0 == 1").unwrap(), "false true\n");
+    let body = "    let a = [5]\n    let i = 1\n    println i < len(a) && a[i] == 5, i >= len(a) || a[i] == 5";
+    assert_eq!(run(body).unwrap(), "false true\n");
+    assert_eq!(runtime_error("    println true && [1][5] == 1"), ("index out of bounds, 5 for a length of 1".to_string(), 2, 24));
+    assert_eq!(runtime_error("    println false || 1 / 0 == 1"), ("division by zero".to_string(), 2, 24));
+}
+
+// an int and a float give a float, abs of the smallest int doesn't fit
+#[test]
+fn min_max_abs() {
+    assert_eq!(run("    println min(1, 2), max(1, 2), abs(-3), abs(3)").unwrap(), "1 2 3 3\n");
+    assert_eq!(run("    println min(1, 2.5), max(1, 2.5), min(-1.5, 2.5), abs(-2.5)").unwrap(), "1.0 2.5 -1.5 2.5\n");
+    assert_eq!(runtime_error("    println abs(-2147483647 - 1)"), ("arithmetic overflow".to_string(), 2, 13));
+    assert_eq!(run("    println abs(-2147483647)").unwrap(), "2147483647\n");
+    match parse_str("fn main() {\n    println max(\"a\", 1)\n}\n", "arith.mpl") {
+        Err(MplError::Type(e)) => assert_eq!((e.message.as_str(), e.pos.col), ("max expects numbers, found string", 17)),
+        other => panic!("{:?}", other.map(|p| p.to_sexpr())),
+    }
+    match parse_str("fn main() {\n    println min(1)\n}\n", "arith.mpl") {
+        Err(MplError::Parse(e)) => assert_eq!(e.to_string(), "Grammar error : min takes 2 argument(s), 1 given at arith.mpl line:col -> (2:13)\n"),
+        other => panic!("{:?}", other.map(|p| p.to_sexpr())),
+    }
+}
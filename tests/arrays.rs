@@ -0,0 +1,36 @@
+use mpl2::{MplError, run_str};
+
+fn run(body: &str) -> Result<String, MplError> {
+    run_str(&format!("fn main() {{\n{}\n}}\n", body), "array.mpl")
+}
+
+// message and column of the runtime error of a main with the given body, the error of an
+// index is on its bracket
+fn runtime_error(body: &str) -> (String, usize) {
+    match run(body) {
+        Err(MplError::Runtime(e)) => (e.message, e.pos.col),
+        other => panic!("{}: {:?}", body, other),
+    }
+}
+
+#[test]
+fn arrays_and_indexing() {
+    let body = "    let a = [10, 20, 30]\n    let m = [[1, 2], [3, 4, 5]]\n    println a, a[0] + a[2], m[1][2], m[0]\n    let i = 1\n    println a[i + 1], [1.5, 2][1]";
+    assert_eq!(run(body).unwrap(), "[10, 20, 30] 40 5 [1, 2]\n30 2.0\n");
+    assert_eq!(run("    let e: [int] = []\n    println e").unwrap(), "[]\n");
+}
+
+#[test]
+fn index_out_of_bounds() {
+    assert_eq!(runtime_error("    let a = [10, 20, 30]\n    println a[3]"), ("index out of bounds, 3 for a length of 3".to_string(), 14));
+    assert_eq!(runtime_error("    let a = [10, 20, 30]\n    println a[-1]"), ("index out of bounds, -1 for a length of 3".to_string(), 14));
+    assert_eq!(runtime_error("    let e: [int] = []\n    println e[0]"), ("index out of bounds, 0 for a length of 0".to_string(), 14));
+    assert_eq!(runtime_error("    println [[1], [2, 3]][0][1]"), ("index out of bounds, 1 for a length of 1".to_string(), 29));
+}
+
+// the number of elements of an array, of chars of a string
+#[test]
+fn len() {
+    let body = "    let m = [[1, 2], [3, 4, 5]]\n    let e: [float] = []\n    println len(m), len(m[1]), len(e), len(\"h\u{e9}llo\"), len(\"\")";
+    assert_eq!(run(body).unwrap(), "2 3 0 5 0\n");
+}
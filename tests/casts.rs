@@ -0,0 +1,47 @@
+use mpl2::{MplError, parse_str, run_str};
+
+fn run(body: &str) -> Result<String, MplError> {
+    run_str(&format!("fn main() {{\n{}\n}}\n", body), "cast.mpl")
+}
+
+#[test]
+fn int_to_float() {
+    assert_eq!(run("    println 3 as float, 7 as float / 2").unwrap(), "3.0 3.5\n");
+}
+
+#[test]
+fn float_to_int_truncates() {
+    assert_eq!(run("    println 2.9 as int, -2.9 as int, 7 / 2.0 as int").unwrap(), "2 -2 3\n");
+}
+
+#[test]
+fn invalid_casts_rejected() {
+    for (body, message) in [
+        ("    println \"1\" as int", "can't cast string to int"),
+        ("    println [1] as int", "can't cast [int] to int"),
+        ("    println 1 as [int]", "can't cast int to [int]"),
+        ("    println true as float", "can't cast bool to float"),
+    ] {
+        match parse_str(&format!("fn main() {{\n{}\n}}\n", body), "cast.mpl") {
+            Err(MplError::Type(e)) => assert_eq!(e.message, message),
+            other => panic!("{}: {:?}", body, other.map(|p| p.to_sexpr())),
+        }
+    }
+}
+
+// a float whose truncation isn't an int is an overflow at the cast, like the int operations
+#[test]
+fn float_to_int_overflow() {
+    assert_eq!(run("    println 2147483647.9 as int, (-2147483648.5) as int").unwrap(), "2147483647 -2147483648\n");
+    for (body, col) in [
+        ("    let x = 1e20\n    println x as int", 15),
+        ("    let x = -1e20\n    println x as int", 15),
+        ("    let x = 2147483648.0\n    println x as int", 15),
+        ("    let x = 0.0 / 0.0\n    println x as int", 15),
+    ] {
+        match run(body) {
+            Err(MplError::Runtime(e)) => assert_eq!((e.message.as_str(), e.pos.line, e.pos.col), ("arithmetic overflow", 3, col), "{}", body),
+            other => panic!("{}: {:?}", body, other),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+mod common;
+
+use common::{mpl, write_files};
+
+const IMPORTING: &[(&str, &str)] = &[
+    ("main.mpl", "import \"util.mpl\"\nfn main() {\n    call hello()\n}\n"),
+    ("util.mpl", "fn hello() {\n    println \"hello\"\n}\n"),
+];
+
+// names of the phases timed on stderr, each line is "phase: x.xxx ms"
+fn timed_phases(err: &str) -> Vec<&str> {
+    err.lines()
+        .map(|line| {
+            let (phase, duration) = line.split_once(": ").unwrap();
+            let ms = duration.strip_suffix(" ms").unwrap();
+            assert!(ms.parse::<f64>().is_ok() && ms.split_once('.').unwrap().1.len() == 3, "{}", line);
+            phase
+        })
+        .collect()
+}
+
+#[test]
+fn time_reports_each_phase() {
+    let path = write_files("time", IMPORTING);
+    let (ok, out, err) = mpl(&["--time", &path], "");
+    assert!(ok);
+    assert_eq!(out, "hello\n");
+    assert_eq!(timed_phases(&err), ["lexing", "import resolution", "parsing"]);
+    let (ok, _, err) = mpl(&["--time", "--lex-only", "--no-imports", &path], "");
+    assert!(ok);
+    assert_eq!(timed_phases(&err), ["lexing"]);
+    let (_, _, err) = mpl(&[&path], "");
+    assert_eq!(err, "");
+}
+
+// the AST is printed before the program runs
+#[test]
+fn ast_option() {
+    let path = write_files("ast", &[("main.mpl", "fn main() {\n    let x = 1\n    println x + 2\n}\n")]);
+    let (ok, out, err) = mpl(&["--ast", &path], "");
+    assert!(ok, "{}", err);
+    assert_eq!(out, "(fn main () (let x 1) (println (+ x 2)))\n3\n");
+    let (ok, out, _) = mpl(&["--ast", "--parse-only", &path], "");
+    assert!(ok);
+    assert_eq!(out, "(fn main () (let x 1) (println (+ x 2)))\n");
+}
+
+#[test]
+fn max_errors_option() {
+    let src: String = (0..25).map(|i| format!("fn f{}() {{\n    let = {}\n}}\n", i, i)).collect::<String>() + "fn main() {\n}\n";
+    let path = write_files("max_errors", &[("main.mpl", &src)]);
+    let (ok, out, err) = mpl(&[&path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    // 20 by default
+    assert_eq!(err.lines().filter(|l| l.starts_with("Grammar error")).count(), 20);
+    assert!(err.ends_with("\u{2026} and more errors\n\n"), "{}", err);
+    let (_, _, err) = mpl(&["--max-errors", "2", &path], "");
+    assert_eq!(
+        err,
+        format!(
+            "Grammar error : Expected variable name, found '=' at {0} line:col -> (2:9)\nGrammar error : Expected variable name, found '=' at {0} line:col -> (5:9)\n\u{2026} and more errors\n\n",
+            path
+        )
+    );
+    for n in ["0", "x", "-1"] {
+        let (ok, _, err) = mpl(&["--max-errors", n, &path], "");
+        assert!(!ok);
+        assert!(err.starts_with("--max-errors expects a positive number\nUsage: "), "{}", err);
+    }
+}
+
+// the diagnostics are reported, the program doesn't run
+#[test]
+fn check_does_not_run() {
+    let path = write_files("check", &[("main.mpl", "fn main() {\n    println \"ran\"\n    eprintln \"ran\"\n    println [1][5]\n}\n")]);
+    assert_eq!(mpl(&["--check", &path], ""), (true, String::new(), String::new()));
+    let (ok, out, err) = mpl(&[&path], "");
+    assert!(!ok);
+    assert_eq!(out, "ran\n");
+    assert!(err.starts_with("ran\n"), "{}", err);
+    let path = write_files("check_error", &[("main.mpl", "fn main() {\n    println \"ran\"\n    println 1 + \"a\"\n}\n")]);
+    let (ok, out, err) = mpl(&["--check", &path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert!(err.starts_with("Type error : "), "{}", err);
+}
+
+const ENTRIES: &str = "fn start() {\n    println \"start\"\n}\nfn main() {\n    println \"main\"\n}\nfn p(a: int) {\n}\n";
+
+#[test]
+fn entry_option() {
+    let path = write_files("entry", &[("main.mpl", ENTRIES)]);
+    assert_eq!(mpl(&["--entry", "start", &path], ""), (true, "start\n".to_string(), String::new()));
+    assert_eq!(mpl(&[&path], ""), (true, "main\n".to_string(), String::new()));
+    // main is not required with another entry
+    let path = write_files("entry_no_main", &[("main.mpl", "fn start() {\n    println 1\n}\n")]);
+    assert_eq!(mpl(&["--entry", "start", &path], ""), (true, "1\n".to_string(), String::new()));
+    let (ok, _, err) = mpl(&[&path], "");
+    assert!(!ok);
+    assert_eq!(err, format!("Grammar error : main function not found at {} line:col -> (4:1)\n\n", path));
+}
+
+#[test]
+fn unknown_entry() {
+    let path = write_files("unknown_entry", &[("main.mpl", ENTRIES)]);
+    let (ok, out, err) = mpl(&["--entry", "nope", &path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert_eq!(err, format!("Grammar error : nope function not found at {} line:col -> (9:1)\n\n", path));
+    let (ok, out, err) = mpl(&["--entry", "p", &path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert_eq!(err, format!("Runtime error : [entry function p can't take parameters] at {} (7:1)\n\n", path));
+    let (ok, _, err) = mpl(&["--entry"], "");
+    assert!(!ok);
+    assert!(err.starts_with("--entry expects a function name\nUsage: "), "{}", err);
+}
+
+// tokens of each kind, imports spliced, most frequent first then by name
+#[test]
+fn stats_option() {
+    let path = write_files("stats", &[("main.mpl", "import \"u.mpl\"\nfn main() {\n    call g()\n    println 1 + 2\n}\n"), ("u.mpl", "fn g() {\n}\n")]);
+    let (ok, out, err) = mpl(&["--stats", &path], "");
+    assert!(ok, "{}", err);
+    let expected = [
+        "     3 LParen", "     3 RParen", "     2 Fn", "     2 Ident", "     2 Integer", "     2 LBrace", "     2 RBrace",
+        "     1 Call", "     1 Eof", "     1 Main", "     1 Plus", "     1 Println", "3",
+    ];
+    assert_eq!(out.lines().collect::<Vec<_>>(), expected);
+    let (ok, out, _) = mpl(&["--stats", "--parse-only", &path], "");
+    assert!(ok);
+    assert_eq!(out.lines().count(), 12);
+}
+
+#[test]
+fn directory_as_source_file() {
+    let dir = write_files("source_dir", &[("sub/main.mpl", "fn main() {\n}\n")]).replace("/main.mpl", "");
+    for args in [vec![dir.as_str()], vec!["--lex-only", &dir], vec!["--check", &dir]] {
+        let (ok, out, err) = mpl(&args, "");
+        assert!(!ok);
+        assert_eq!(out, "");
+        assert_eq!(err, format!("Token error : [expected a file, found a directory {0}] at {0} (1:1)\n\n", dir));
+    }
+    let missing = dir.replace("/sub", "/missing.mpl");
+    let (ok, _, err) = mpl(&[&missing], "");
+    assert!(!ok);
+    assert_eq!(err, format!("Token error : [File not found {0}] at {0} (1:1)\n\n", missing));
+}
+
+// the tokens of each imported file between markers at its import statement, the program
+// runs the same
+#[test]
+fn import_markers_option() {
+    let path = write_files("import_markers", &[("main.mpl", "import \"u.mpl\"\nfn main() {\n    call u()\n}\n"), ("u.mpl", "fn u() {\n    println 1\n}\n")]);
+    let u = path.replace("main.mpl", "u.mpl");
+    let (ok, out, err) = mpl(&["--lex-only", "--tokens", "--import-markers", &path], "");
+    assert!(ok, "{}", err);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], format!(" 1 -> {}:1:1 [ImportStart({:?})]", path, u));
+    assert_eq!(lines[1], format!(" 2 -> {}:1:1 [Fn]", u));
+    assert_eq!(lines[9], format!("10 -> {}:1:1 [ImportEnd({:?})]", path, u));
+    assert_eq!(lines[10], format!("11 -> {}:2:1 [Fn]", path));
+    let (_, without, _) = mpl(&["--lex-only", "--tokens", &path], "");
+    assert!(!without.contains("ImportStart") && !without.contains("ImportEnd"));
+    assert_eq!(without.lines().count() + 2, lines.len());
+    assert_eq!(mpl(&["--import-markers", &path], ""), (true, "1\n".to_string(), String::new()));
+}
+
+// --lex-only stops after the tokens, --parse-only after the syntax tree: the later errors
+// aren't reported and the program doesn't run
+#[test]
+fn lex_only_and_parse_only() {
+    let path = write_files("stop_early", &[("main.mpl", "fn main() {\n    println \"ran\"\n    println x\n}\n")]);
+    assert_eq!(mpl(&["--lex-only", &path], ""), (true, String::new(), String::new()));
+    assert_eq!(mpl(&["--parse-only", &path], ""), (true, String::new(), String::new()));
+    let path = write_files("stop_early_grammar", &[("main.mpl", "fn main() {\n    let = 1\n}\n")]);
+    assert_eq!(mpl(&["--lex-only", &path], ""), (true, String::new(), String::new()));
+    let (ok, _, err) = mpl(&["--parse-only", &path], "");
+    assert!(!ok);
+    assert_eq!(err, format!("Grammar error : Expected variable name, found '=' at {} line:col -> (2:9)\n\n", path));
+    let path = write_files("stop_early_token", &[("main.mpl", "fn main() {\n    println 1 @\n}\n")]);
+    let (ok, _, err) = mpl(&["--lex-only", &path], "");
+    assert!(!ok);
+    assert!(err.starts_with("Token error : "), "{}", err);
+    let (ok, out, err) = mpl(&["--lex-only", "--parse-only", &path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert!(err.starts_with("--lex-only and --parse-only can't be used together\nUsage: "), "{}", err);
+}
+
+// each rule entered and left with the current token, nested by depth, on stderr
+#[test]
+fn parse_trace_option() {
+    let path = write_files("parse_trace", &[("main.mpl", "fn main() {\n    println 1\n}\n")]);
+    let (ok, out, err) = mpl(&["--parse-trace", &path], "");
+    assert!(ok);
+    assert_eq!(out, "1\n");
+    let lines: Vec<&str> = err.lines().collect();
+    assert_eq!(lines.len(), 20);
+    assert_eq!(
+        lines[..4],
+        ["enter parse_function @ Fn", "  enter parse_block @ LBrace", "    enter parse_statement @ Println", "      enter parse_expr @ Integer(1)"]
+    );
+    assert_eq!(lines[8], "                enter parse_primary @ Integer(1)");
+    assert_eq!(lines[9], "                exit parse_primary @ RBrace");
+    assert_eq!(lines[18..], ["  exit parse_block @ Eof", "exit parse_function @ Eof"]);
+    assert_eq!(mpl(&[&path], ""), (true, "1\n".to_string(), String::new()));
+}
+
+// the imports stay as tokens of the main file, the imported files aren't read
+#[test]
+fn no_imports_option() {
+    let path = write_files("no_imports", &[("main.mpl", "import \"missing.mpl\"\nfn main() {\n}\n")]);
+    let (ok, out, err) = mpl(&["--no-imports", "--lex-only", "--tokens", &path], "");
+    assert!(ok, "{}", err);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[..3], [format!("1 -> {}:1:1 [Import]", path), format!("2 -> {}:1:8 [Str(\"missing.mpl\")]", path), format!("3 -> {}:2:1 [Fn]", path)]);
+    assert_eq!(lines[8..], [format!("9 -> {}:4:1 [Eof]", path), String::new()]);
+    let (ok, _, err) = mpl(&["--lex-only", &path], "");
+    assert!(!ok);
+    assert!(err.starts_with("Token error : [File not found "), "{}", err);
+    // the parser doesn't take an import left in the tokens
+    let (ok, _, err) = mpl(&["--no-imports", "--parse-only", &path], "");
+    assert!(!ok);
+    assert_eq!(err, format!("Grammar error : Expected function declaration, found 'import' at {} line:col -> (1:1)\n\n", path));
+}
@@ -0,0 +1,51 @@
+// helpers shared by the integration tests, each test crate uses some of them
+#![allow(dead_code)]
+
+use std::rc::Rc;
+
+use mpl2::import::MemoryResolver;
+use mpl2::lexer::Lexer;
+
+// lexer of the first file, the others can be imported by it
+pub fn lexer(files: &[(&str, &str)]) -> Lexer {
+    let mut resolver = MemoryResolver::new();
+    for (path, src) in files {
+        resolver.insert(*path, *src);
+    }
+    Lexer::with_resolver(files[0].0.to_string(), Rc::new(resolver))
+}
+
+// run the mpl2 binary from the crate directory, the input is written to its stdin. Returns
+// the exit success, stdout and stderr
+pub fn mpl(args: &[&str], input: &str) -> (bool, String, String) {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mpl2"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+// write the files in a directory of the temp dir named after the test, returns the path of
+// the first one. The directory is emptied first
+pub fn write_files(test: &str, files: &[(&str, &str)]) -> String {
+    let dir = std::env::temp_dir().join(format!("mpl2_{}_{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    for (path, src) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, src).unwrap();
+    }
+    dir.join(files[0].0).to_str().unwrap().to_string()
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use mpl2::lexer::Lexer;
+
+use common::{mpl, write_files};
+
+const INVALID: &[u8] = b"fn main() {\n    println \"caf\xe9\"\n}\n";
+
+#[test]
+fn invalid_utf8_main_file() {
+    let path = write_files("invalid_utf8", &[("main.mpl", "")]);
+    std::fs::write(&path, INVALID).unwrap();
+    let e = Lexer::new(path.clone()).tokenize().unwrap_err();
+    assert_eq!(e.message, format!("source file is not valid UTF-8 {}", path));
+    assert_eq!((e.pos.line, e.pos.col), (1, 1));
+    let (ok, out, err) = mpl(&[&path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert_eq!(err, format!("Token error : [source file is not valid UTF-8 {}] at {} (1:1)\n\n", path, path));
+}
+
+// reported at the import statement of the file
+#[test]
+fn invalid_utf8_imported_file() {
+    let path = write_files("invalid_utf8_import", &[("main.mpl", "\nimport \"latin1.mpl\"\nfn main() {\n}\n"), ("latin1.mpl", "")]);
+    let latin1 = path.replace("main.mpl", "latin1.mpl");
+    std::fs::write(&latin1, INVALID).unwrap();
+    let e = Lexer::new(path.clone()).tokenize().unwrap_err();
+    assert_eq!(e.message, format!("source file is not valid UTF-8 {}", latin1));
+    assert_eq!((e.pos.file_name.as_str(), e.pos.line), (path.as_str(), 2));
+}
+
+// utf-8 is the only encoding, under either spelling and any case
+#[test]
+fn encoding_option() {
+    let path = write_files("encoding", &[("main.mpl", "fn main() {\n    println \"café\"\n}\n")]);
+    for name in ["utf-8", "UTF-8", "utf8", "Utf8"] {
+        assert_eq!(mpl(&["--encoding", name, &path], ""), (true, "café\n".to_string(), String::new()), "{}", name);
+    }
+    let (ok, out, err) = mpl(&["--encoding", "latin1", &path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert_eq!(err, "unsupported encoding latin1, only utf-8 is supported\n");
+    let (ok, _, err) = mpl(&["--encoding"], "");
+    assert!(!ok);
+    assert!(err.starts_with("--encoding expects an encoding name\nUsage: "), "{}", err);
+}
@@ -0,0 +1,42 @@
+use mpl2::fold;
+use mpl2::parse_str;
+
+// the S-expression of main once folded
+fn folded(body: &str) -> String {
+    let mut program = parse_str(&format!("fn main() {{\n{}\n}}\n", body), "fold.mpl").unwrap();
+    fold::fold_program(&mut program);
+    program.to_sexpr()
+}
+
+#[test]
+fn string_concatenation() {
+    assert_eq!(folded("    println \"a\" + \"b\""), "(fn main () (println \"ab\"))");
+}
+
+#[test]
+fn string_length() {
+    assert_eq!(folded("    println len(\"abc\")"), "(fn main () (println 3))");
+}
+
+#[test]
+fn numbers_and_booleans() {
+    assert_eq!(folded("    println 1 + 2 * 3, -(2.5), 1 < 2 && true"), "(fn main () (println 7 -2.5 true))");
+    assert_eq!(folded("    println 1 + 2.5"), "(fn main () (println 3.5))");
+}
+
+#[test]
+fn variables_not_folded() {
+    assert_eq!(folded("    let x = 1\n    println x + 2 * 3"), "(fn main () (let x 1) (println (+ x 6)))");
+}
+
+#[test]
+fn failing_operations_not_folded() {
+    assert_eq!(folded("    println 2147483647 + 1"), "(fn main () (println (+ 2147483647 1)))");
+    assert_eq!(folded("    println 1 / 0"), "(fn main () (println (/ 1 0)))");
+}
+
+#[test]
+fn same_output_once_folded() {
+    let src = "fn main() {\n    println \"a\" + \"b\", len(\"abc\"), 7 / 2, 1.5 * 2\n}\n";
+    assert_eq!(mpl2::run_str(src, "fold.mpl").unwrap(), "ab 3 3 3.0\n");
+}
@@ -0,0 +1,46 @@
+use mpl2::{MplError, parse_str, run_str};
+use mpl2::ast::Program;
+
+#[test]
+fn parse_valid_program() {
+    let program: Program = "fn main() {\n    println 1 + 2\n}\n".parse().unwrap();
+    assert_eq!(program.functions().count(), 1);
+}
+
+#[test]
+fn parse_invalid_program() {
+    let result = "fn main() {\n    println 1 +\n}\n".parse::<Program>();
+    assert!(matches!(result, Err(MplError::Parse(_))), "{:?}", result.err());
+}
+
+// parse_str stops at the first phase failing, run_str also runs the program and returns
+// what it printed
+#[test]
+fn each_phase_error() {
+    let main = |body: &str| format!("fn main() {{\n{}\n}}\n", body);
+    assert_eq!(run_str(&main("    println 1 + 2\n    print \"a\""), "s.mpl").unwrap(), "3\na");
+    assert_eq!(parse_str(&main("    println 1"), "s.mpl").unwrap().to_sexpr(), "(fn main () (println 1))");
+    for (body, phase) in [
+        ("    println 1 @", "Token error"),
+        ("    println 1 +", "Grammar error"),
+        ("    println x", "Resolve error"),
+        ("    println 1 + \"a\"", "Type error"),
+    ] {
+        let e = parse_str(&main(body), "s.mpl").unwrap_err();
+        let variant = match &e {
+            MplError::Lex(_) => "Token error",
+            MplError::Parse(_) => "Grammar error",
+            MplError::Resolve(_) => "Resolve error",
+            MplError::Type(_) => "Type error",
+            MplError::Runtime(_) => "Runtime error",
+        };
+        assert_eq!(variant, phase, "{}", body);
+        // displayed as the error of the phase, which is its source
+        assert!(e.to_string().starts_with(&format!("{} : ", phase)), "{}", e);
+        assert_eq!(std::error::Error::source(&e).map(|s| s.to_string()), Some(e.to_string()));
+    }
+    let e = run_str(&main("    println [1][2]"), "s.mpl").unwrap_err();
+    assert!(matches!(e, MplError::Runtime(_)), "{:?}", e);
+    assert_eq!(e.to_string(), "Runtime error : [index out of bounds, 2 for a length of 1] at s.mpl (2:16)\n");
+    assert!(parse_str(&main("    println [1][2]"), "s.mpl").is_ok());
+}
@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::Path;
+
+use mpl2::MplError;
+use mpl2::eval::Interpreter;
+use mpl2::parser::Parser;
+use mpl2::resolve::Resolver;
+use mpl2::typeck::TypeChecker;
+
+// what a program prints on stdout, or the error that stopped it
+fn run(path: &Path) -> String {
+    let mut out = Vec::new();
+    let result = (|| -> Result<(), MplError> {
+        let program = Parser::new().parse(path.to_string_lossy().into_owned())?;
+        Resolver::new().resolve(&program)?;
+        TypeChecker::new(false).check(&program)?;
+        Interpreter::with_output(Box::new(&mut out), Box::new(std::io::sink())).run(&program)?;
+        Ok(())
+    })();
+    let mut text = String::from_utf8_lossy(&out).into_owned();
+    if let Err(e) = result {
+        text += &e.to_string();
+    }
+    text
+}
+
+// every tests/cases/X.mpl prints what tests/cases/X.expected holds. The tests run from the
+// crate directory, the relative paths keep the positions of the errors the same on any machine
+#[test]
+fn golden_cases() {
+    let dir = Path::new("tests").join("cases");
+    let mut cases: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mpl"))
+        .collect();
+    cases.sort();
+    assert!(!cases.is_empty(), "no case in {}", dir.display());
+    let mut failures = Vec::new();
+    for case in &cases {
+        let expected = fs::read_to_string(case.with_extension("expected")).unwrap_or_default();
+        let found = run(case);
+        if found != expected {
+            failures.push(format!("{}\n--- expected\n{}--- found\n{}", case.display(), expected, found));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use mpl2::highlight::highlight;
+
+use common::mpl;
+
+#[test]
+fn colors_by_token_kind() {
+    let src = "fn main() {\n    println \"hi\", 1 // c\n}\n";
+    assert_eq!(
+        highlight(src, "h.mpl").unwrap(),
+        "\x1b[1mfn\x1b[0m \x1b[1mmain\x1b[0m() {\n    \x1b[1mprintln\x1b[0m \x1b[32m\"hi\"\x1b[0m, \x1b[36m1\x1b[0m \x1b[90m// c\x1b[0m\n}\n"
+    );
+}
+
+#[test]
+fn directory_reported_as_a_lex_error() {
+    let (ok, out, err) = mpl(&["--highlight", "tests"], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert_eq!(err, "Token error : [expected a file, found a directory tests] at tests (1:1)\n\n");
+}
@@ -0,0 +1,288 @@
+mod common;
+
+use std::collections::HashMap;
+
+use mpl2::lexer::{Lexer, LexerOptions, Position, TokenStream};
+use mpl2::parser::Parser;
+use mpl2::token::Token;
+
+use common::{lexer, write_files};
+
+// names of the functions of the program made of the files, the first one is the main file
+fn functions(files: &[(&str, &str)]) -> Result<Vec<String>, String> {
+    let tokens = lexer(files).tokenize().map_err(|e| e.message)?;
+    let program = Parser::from_stream(TokenStream { tokens }).parse_tokens().map_err(|e| e.to_string())?;
+    Ok(program.functions().map(|f| f.name.clone()).collect())
+}
+
+fn graph(edges: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+    edges.iter().map(|(file, imports)| (file.to_string(), imports.iter().map(|i| i.to_string()).collect())).collect()
+}
+
+#[test]
+fn graph_of_a_chain() {
+    let mut lexer = lexer(&[
+        ("a.mpl", "import \"b.mpl\"\nfn main() {\n    call b()\n}\n"),
+        ("b.mpl", "import \"c.mpl\"\nfn b() {\n    call c()\n}\n"),
+        ("c.mpl", "fn c() {\n}\n"),
+    ]);
+    lexer.tokenize().unwrap();
+    assert_eq!(lexer.import_graph(), graph(&[("a.mpl", &["b.mpl"]), ("b.mpl", &["c.mpl"]), ("c.mpl", &[])]));
+}
+
+#[test]
+fn diamond_spliced_once() {
+    let files = [
+        ("a.mpl", "import \"b.mpl\"\nimport \"c.mpl\"\nfn main() {\n    call b()\n    call c()\n}\n"),
+        ("b.mpl", "import \"d.mpl\"\nfn b() {\n    call d()\n}\n"),
+        ("c.mpl", "import \"d.mpl\"\nfn c() {\n    call d()\n}\n"),
+        ("d.mpl", "fn d() {\n}\n"),
+    ];
+    assert_eq!(functions(&files).unwrap(), ["d", "b", "c", "main"]);
+    let mut lexer = lexer(&files);
+    lexer.tokenize().unwrap();
+    assert_eq!(
+        lexer.import_graph(),
+        graph(&[("a.mpl", &["b.mpl", "c.mpl"]), ("b.mpl", &["d.mpl"]), ("c.mpl", &["d.mpl"]), ("d.mpl", &[])])
+    );
+}
+
+#[test]
+fn cycle_rejected() {
+    let files = [
+        ("a.mpl", "import \"b.mpl\"\nfn main() {\n}\n"),
+        ("b.mpl", "import \"c.mpl\"\nfn b() {\n}\n"),
+        ("c.mpl", "import \"b.mpl\"\nfn c() {\n}\n"),
+    ];
+    assert_eq!(functions(&files).unwrap_err(), "import cycle a.mpl -> b.mpl -> c.mpl -> b.mpl");
+}
+
+// a file first imported with from, then again through another file, brings its other functions
+#[test]
+fn selective_then_whole_import() {
+    let files = [
+        ("a.mpl", "from \"f.mpl\" import foo\nimport \"g.mpl\"\nfn main() {\n    call foo()\n    call g()\n}\n"),
+        ("f.mpl", "fn foo() {\n}\nfn bar() {\n}\n"),
+        ("g.mpl", "import \"f.mpl\"\nfn g() {\n    call bar()\n}\n"),
+    ];
+    assert_eq!(functions(&files).unwrap(), ["foo", "bar", "g", "main"]);
+}
+
+#[test]
+fn whole_then_selective_import() {
+    let files = [
+        ("a.mpl", "import \"g.mpl\"\nfrom \"f.mpl\" import foo\nfn main() {\n    call foo()\n    call g()\n}\n"),
+        ("f.mpl", "fn foo() {\n}\nfn bar() {\n}\n"),
+        ("g.mpl", "import \"f.mpl\"\nfn g() {\n    call bar()\n}\n"),
+    ];
+    assert_eq!(functions(&files).unwrap(), ["foo", "bar", "g", "main"]);
+}
+
+// the spliced tokens keep the file, line and column they have in the imported file
+#[test]
+fn imported_tokens_keep_their_positions() {
+    let mut lexer = lexer(&[
+        ("a.mpl", "// main file\n\nimport \"b.mpl\"\nfn main() {\n    call b()\n}\n"),
+        ("b.mpl", "fn b() {\n    println 1\n}\n"),
+    ]);
+    let tokens = lexer.tokenize().unwrap();
+    let println = tokens.iter().find(|t| t.token == Token::Println).unwrap();
+    assert_eq!(println.pos, Position { file_name: "b.mpl".to_string(), line: 2, col: 5 });
+    let main = tokens.iter().find(|t| t.token == Token::Main).unwrap();
+    assert_eq!(main.pos, Position { file_name: "a.mpl".to_string(), line: 4, col: 4 });
+}
+
+// comments after or between the import statements don't break the grouping, whether they are
+// skipped or kept as tokens
+#[test]
+fn commented_imports() {
+    let files = [
+        ("a.mpl", "import \"b.mpl\" // v2\n/* utils */\nimport \"c.mpl\" /* v1 */\nfn main() {\n    call b()\n    call c()\n}\n"),
+        ("b.mpl", "fn b() {\n}\n"),
+        ("c.mpl", "fn c() {\n}\n"),
+    ];
+    assert_eq!(functions(&files).unwrap(), ["b", "c", "main"]);
+    let mut lexer = lexer(&files);
+    lexer.set_keep_trivia(true);
+    let tokens = lexer.tokenize().unwrap();
+    assert!(tokens.iter().any(|t| t.token == Token::Comment("// v2".to_string())));
+    assert_eq!(lexer.import_graph()["a.mpl"], ["b.mpl", "c.mpl"]);
+}
+
+#[test]
+fn commented_out_import() {
+    let files = [("a.mpl", "// import \"b.mpl\"\nfn main() {\n}\n")];
+    assert_eq!(functions(&files).unwrap(), ["main"]);
+    let mut lexer = lexer(&files);
+    lexer.set_keep_trivia(true);
+    lexer.tokenize().unwrap();
+    assert!(lexer.import_graph()["a.mpl"].is_empty());
+}
+
+// the options of the lexer apply to the imported files as well
+#[test]
+fn options_apply_to_imported_files() {
+    let mut lexer = lexer(&[("a.mpl", "import \"b.mpl\"\nfn main() {\n}\n"), ("b.mpl", "fn b() {\n\tprintln 1\n}\n")]);
+    lexer.set_options(LexerOptions { warn_tabs: true, import_markers: true, ..LexerOptions::default() });
+    let tokens = lexer.tokenize().unwrap();
+    let warned: Vec<(&str, usize)> = lexer.warnings().iter().map(|w| (w.pos.file_name.as_str(), w.pos.line)).collect();
+    assert_eq!(warned, vec![("b.mpl", 2)]);
+    assert!(tokens.iter().any(|t| matches!(t.token, Token::ImportStart(_))));
+}
+
+#[test]
+fn import_after_declaration_rejected_whatever_the_layout() {
+    for main in [
+        "fn main() {\n}\nimport \"u.mpl\"\n",
+        "fn main() {\n}\n\n\nimport\n\n\"u.mpl\"\n",
+        "fn main() {\n}\n// late\nimport // the file\n\"u.mpl\"\n",
+        "fn main() {\n} import /* */ \"u.mpl\"\n",
+    ] {
+        let message = lexer(&[("a.mpl", main), ("u.mpl", "fn u() {\n}\n")]).tokenize().map(|_| ()).unwrap_err().message;
+        assert_eq!(message, "import can't be after instruction", "{:?}", main);
+    }
+}
+
+#[test]
+fn imports_separated_by_blank_lines_and_comments() {
+    let main = "import \"u.mpl\"\n\n// next one\n\nimport // the file\n\n\"v.mpl\"\nfn main() {\n}\n";
+    let files = [("a.mpl", main), ("u.mpl", "fn u() {\n}\n"), ("v.mpl", "fn v() {\n}\n")];
+    assert_eq!(functions(&files).unwrap(), ["u", "v", "main"]);
+}
+
+// functions of the program whose files are written on the disk, the first one is the main file
+fn functions_on_disk(test: &str, files: &[(&str, &str)]) -> Result<Vec<String>, String> {
+    let path = write_files(test, files);
+    let tokens = Lexer::new(path).tokenize().map_err(|e| e.message)?;
+    let program = Parser::from_stream(TokenStream { tokens }).parse_tokens().map_err(|e| e.to_string())?;
+    Ok(program.functions().map(|f| f.name.clone()).collect())
+}
+
+#[test]
+fn directory_imported_through_its_index_file() {
+    let main = ("main.mpl", "import \"math\"\nfn main() {\n}\n");
+    let found = functions_on_disk("dir_main", &[main, ("math/main.mpl", "fn sq() {\n}\n"), ("math/other.mpl", "fn other() {\n}\n")]);
+    assert_eq!(found.unwrap(), ["sq", "main"]);
+    let found = functions_on_disk("dir_mod", &[main, ("math/mod.mpl", "fn cube() {\n}\n")]);
+    assert_eq!(found.unwrap(), ["cube", "main"]);
+    // main.mpl is looked up first
+    let found = functions_on_disk("dir_both", &[main, ("math/mod.mpl", "fn cube() {\n}\n"), ("math/main.mpl", "fn sq() {\n}\n")]);
+    assert_eq!(found.unwrap(), ["sq", "main"]);
+}
+
+#[test]
+fn directory_without_index_file() {
+    let path = write_files("dir_no_index", &[("main.mpl", "import \"math\"\nfn main() {\n}\n"), ("math/util.mpl", "fn u() {\n}\n")]);
+    let e = Lexer::new(path.clone()).tokenize().unwrap_err();
+    let dir = path.replace("main.mpl", "math");
+    assert_eq!(e.message, format!("directory {} has no main.mpl or mod.mpl", dir));
+    assert_eq!((e.pos.line, e.pos.col), (1, 1));
+}
+
+#[test]
+fn directory_and_file_of_the_same_name() {
+    let files = [("main.mpl", "import \"math\"\nfn main() {\n}\n"), ("math.mpl", "fn f() {\n}\n"), ("math/main.mpl", "fn sq() {\n}\n")];
+    let message = functions_on_disk("dir_ambiguous", &files).unwrap_err();
+    let dir = write_files("dir_ambiguous", &files).replace("main.mpl", "math");
+    assert_eq!(message, format!("import {} is ambiguous, both {}.mpl and directory {} exist", dir, dir, dir));
+}
+
+// reported at the import statement, the message gives the line of the statement
+#[test]
+fn top_level_statement_in_imported_file() {
+    let main = "\nimport \"b.mpl\"\nfn main() {\n}\n";
+    for (imported, found) in [
+        ("println \"hi\"\nfn b() {\n}\n", "'println' at line 1"),
+        ("fn b() {\n}\nlet x = 1\n", "'let' at line 3"),
+        ("fn b() {\n    println 1\n}\n// done\n\ncall b()\n", "'call' at line 6"),
+    ] {
+        let e = lexer(&[("a.mpl", main), ("b.mpl", imported)]).tokenize().map(|_| ()).unwrap_err();
+        assert_eq!(e.message, format!("imported file b.mpl can only declare functions, found {}", found));
+        assert_eq!((e.pos.file_name.as_str(), e.pos.line, e.pos.col), ("a.mpl", 2, 1));
+    }
+    // statements in the function bodies and nested imports are fine
+    let files = [("a.mpl", main), ("b.mpl", "import \"c.mpl\"\nfn b() {\n    println 1\n}\n"), ("c.mpl", "fn c() {\n}\n")];
+    assert_eq!(functions(&files).unwrap(), ["c", "b", "main"]);
+}
+
+// '/' and '\' both separate the directories of an import path, which is relative to the file
+// importing it. A main file without a directory imports from the current one
+#[test]
+fn import_path_separators() {
+    let files = [
+        ("main.mpl", "import \"lib/a.mpl\"\nimport \"lib\\\\b.mpl\"\nfn main() {\n}\n"),
+        ("lib/a.mpl", "fn a() {\n}\n"),
+        ("lib/b.mpl", "import \"deep\\\\c.mpl\"\nfn b() {\n}\n"),
+        ("lib/deep/c.mpl", "fn c() {\n}\n"),
+    ];
+    assert_eq!(functions(&files).unwrap(), ["a", "c", "b", "main"]);
+    let mut lexer = lexer(&files);
+    lexer.tokenize().unwrap();
+    let imported: Vec<&str> = lexer.imports().iter().map(|i| i.file_name.as_str()).collect();
+    assert_eq!(imported, ["lib/a.mpl", "lib/b.mpl"]);
+    assert_eq!(lexer.import_graph()["lib/b.mpl"], ["lib/deep/c.mpl"]);
+}
+
+// a comment of any kind, on one or several lines, between import and its file name
+#[test]
+fn comment_between_import_and_its_file_name() {
+    for main in [
+        "import /* the file */ \"u.mpl\"\nfn main() {\n}\n",
+        "import /* the\nfile */ \"u.mpl\"\nfn main() {\n}\n",
+        "import // the file\n\"u.mpl\"\nfn main() {\n}\n",
+        "import \"v.mpl\" /* between\nthe imports */ import\n/* u */ \"u.mpl\"\nfn main() {\n}\n",
+    ] {
+        let files = [("a.mpl", main), ("u.mpl", "fn u() {\n}\n"), ("v.mpl", "fn v() {\n}\n")];
+        let found = functions(&files).unwrap();
+        assert!(found.contains(&"u".to_string()) && found.ends_with(&["main".to_string()]), "{:?}: {:?}", main, found);
+    }
+}
+
+// only the listed functions are spliced, a name the file doesn't declare is an error at that name
+#[test]
+fn selective_import_of_several_names() {
+    let f = ("f.mpl", "fn a() {\n}\nfn b() {\n}\nfn c() {\n}\n");
+    let main = ("main.mpl", "from \"f.mpl\" import a, b\nfn main() {\n    call a()\n    call b()\n}\n");
+    assert_eq!(functions(&[main, f]).unwrap(), ["a", "b", "main"]);
+    let main = ("main.mpl", "from \"f.mpl\" import a, d\nfn main() {\n}\n");
+    let e = lexer(&[main, f]).tokenize().unwrap_err();
+    assert_eq!((e.message.as_str(), e.pos.file_name.as_str(), e.pos.line, e.pos.col), ("function d is not defined in f.mpl", "main.mpl", 1, 24));
+}
+
+// the spliced functions don't make a program, the error is at the end of the main file
+#[test]
+fn imports_only_main_file() {
+    let u = ("u.mpl", "fn u() {\n}\n");
+    assert_eq!(functions(&[("main.mpl", "import \"u.mpl\"\n"), u]).unwrap_err(), "Grammar error : main function not found at main.mpl line:col -> (2:1)\n");
+    assert_eq!(
+        functions(&[("main.mpl", "import \"u.mpl\"\n\n// only imports\n"), u]).unwrap_err(),
+        "Grammar error : main function not found at main.mpl line:col -> (4:1)\n"
+    );
+}
+
+// the file name right after the keyword is imported the same
+#[test]
+fn import_keyword_touching_its_file_name() {
+    let u = ("u.mpl", "fn u() {\n}\nfn v() {\n}\n");
+    assert_eq!(functions(&[("main.mpl", "import\"u.mpl\"\nfn main() {\n}\n"), u]).unwrap(), ["u", "v", "main"]);
+    assert_eq!(functions(&[("main.mpl", "from\"u.mpl\"import v\nfn main() {\n}\n"), u]).unwrap(), ["v", "main"]);
+}
+
+// the extra words are keywords in the main file and in the imported ones, the built-in
+// keywords stay valid
+#[test]
+fn custom_keywords() {
+    let files = [
+        ("main.mpl", "importer \"u.mpl\"\nfonction main() {\n    appeler u()\n    println 1\n}\n"),
+        ("u.mpl", "fonction u() {\n    imprimer \"u\"\n}\n"),
+    ];
+    let french = [("importer", Token::Import), ("fonction", Token::Fn), ("appeler", Token::Call), ("imprimer", Token::Println)];
+    let mut lexer = lexer(&files);
+    lexer.set_keywords(french.iter().map(|(word, token)| (word.to_string(), token.clone())).collect());
+    let tokens = lexer.tokenize().unwrap();
+    let program = Parser::from_stream(TokenStream { tokens }).parse_tokens().unwrap();
+    assert_eq!(program.to_sexpr(), "(fn u () (println \"u\"))\n(fn main () (call u) (println 1))");
+    // without them the words are identifiers
+    let tokens = Lexer::tokenize_source("imprimer \"u\"", "k.mpl").unwrap().tokens;
+    assert_eq!(tokens[0].token, Token::Ident("imprimer".to_string()));
+}
@@ -0,0 +1,309 @@
+use mpl2::lexer::{LexError, Lexer};
+use mpl2::token::Token;
+use mpl2::{parse_str, run_str};
+
+// tokens of a source with their line and column, without the eof token
+fn tokens(src: &str) -> Vec<(Token, usize, usize)> {
+    let mut tokens: Vec<_> = Lexer::tokenize_source(src, "l.mpl")
+        .unwrap()
+        .tokens
+        .into_iter()
+        .map(|t| (t.token, t.pos.line, t.pos.col))
+        .collect();
+    assert_eq!(tokens.pop().map(|t| t.0), Some(Token::Eof));
+    tokens
+}
+
+fn lex_error(src: &str) -> LexError {
+    match Lexer::tokenize_source(src, "l.mpl") {
+        Ok(stream) => panic!("{:?} lexed: {}", src, stream),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn trailing_comment_ignored() {
+    assert_eq!(
+        tokens("let x = 1 // note\nlet y = x// glued\n"),
+        [
+            (Token::Let, 1, 1),
+            (Token::Ident("x".to_string()), 1, 5),
+            (Token::Equal, 1, 7),
+            (Token::Integer(1), 1, 9),
+            (Token::Let, 2, 1),
+            (Token::Ident("y".to_string()), 2, 5),
+            (Token::Equal, 2, 7),
+            (Token::Ident("x".to_string()), 2, 9),
+        ]
+    );
+}
+
+#[test]
+fn consecutive_comments_ignored() {
+    assert_eq!(
+        tokens("// one\n// two\n\n   // three\nnext // four // five\n// six"),
+        [(Token::Next, 5, 1)]
+    );
+    assert_eq!(tokens("break /* a */ /* b */ // c\n/* d */ next"), [(Token::Break, 1, 1), (Token::Next, 2, 9)]);
+}
+
+#[test]
+fn hex_floats() {
+    for (src, value) in [("0x1.8p3", 12.0), ("0x1p-2", 0.25), ("0XAp+1", 20.0), ("0x.8p0", 0.5), ("0x1_0p0", 16.0)] {
+        assert_eq!(tokens(src), [(Token::Float(value), 1, 1)], "{}", src);
+    }
+}
+
+#[test]
+fn malformed_hex_floats() {
+    for src in ["0x1.8", "0x1.8p", "0x1.8p+", "0x1.8pz", "0x1.gp3", "0x10", "0xp3"] {
+        let e = lex_error(&format!("let x = {}", src));
+        assert_eq!(e.message, format!("invalid hexadecimal float format [{}]", src));
+        assert_eq!((e.pos.line, e.pos.col), (1, 9), "{}", src);
+    }
+}
+
+// a two-char operator is one token, positioned on its first char
+#[test]
+fn two_char_operators() {
+    assert_eq!(
+        tokens("a<=b >= c==d!=e && f||g = h < i"),
+        [
+            (Token::Ident("a".to_string()), 1, 1),
+            (Token::Le, 1, 2),
+            (Token::Ident("b".to_string()), 1, 4),
+            (Token::Ge, 1, 6),
+            (Token::Ident("c".to_string()), 1, 9),
+            (Token::EqEq, 1, 10),
+            (Token::Ident("d".to_string()), 1, 12),
+            (Token::NotEq, 1, 13),
+            (Token::Ident("e".to_string()), 1, 15),
+            (Token::AndAnd, 1, 17),
+            (Token::Ident("f".to_string()), 1, 20),
+            (Token::OrOr, 1, 21),
+            (Token::Ident("g".to_string()), 1, 23),
+            (Token::Equal, 1, 25),
+            (Token::Ident("h".to_string()), 1, 27),
+            (Token::Lt, 1, 29),
+            (Token::Ident("i".to_string()), 1, 31),
+        ]
+    );
+    assert_eq!(tokens("x\n  <=1"), [(Token::Ident("x".to_string()), 1, 1), (Token::Le, 2, 3), (Token::Integer(1), 2, 5)]);
+}
+
+// reported at the backslash
+#[test]
+fn backslash_in_identifier() {
+    for (src, col) in [("foo\\bar", 4), ("let a\\b = 1", 6), ("x\\", 2), ("\\x", 1)] {
+        let e = lex_error(src);
+        assert_eq!(e.message, "unexpected '\\' in identifier", "{}", src);
+        assert_eq!((e.pos.line, e.pos.col), (1, col), "{}", src);
+    }
+}
+
+fn ident(name: &str) -> Token {
+    Token::Ident(name.to_string())
+}
+
+// a word stops at the first symbol, which is lexed on its own
+#[test]
+fn identifiers_followed_by_symbols() {
+    assert_eq!(tokens("foo+bar"), [(ident("foo"), 1, 1), (Token::Plus, 1, 4), (ident("bar"), 1, 5)]);
+    assert_eq!(
+        tokens("foo(bar)"),
+        [(ident("foo"), 1, 1), (Token::LParen, 1, 4), (ident("bar"), 1, 5), (Token::RParen, 1, 8)]
+    );
+    assert_eq!(tokens("a.b"), [(ident("a"), 1, 1), (Token::Dot, 1, 2), (ident("b"), 1, 3)]);
+    assert_eq!(tokens("foo.bar."), [(ident("foo"), 1, 1), (Token::Dot, 1, 4), (ident("bar"), 1, 5), (Token::Dot, 1, 8)]);
+    assert_eq!(tokens("foo123"), [(ident("foo123"), 1, 1)]);
+    assert_eq!(tokens("x[1]"), [(ident("x"), 1, 1), (Token::LBracket, 1, 2), (Token::Integer(1), 1, 3), (Token::RBracket, 1, 4)]);
+}
+
+// '_' groups digits, between two of them only
+#[test]
+fn digit_grouping() {
+    assert_eq!(tokens("1_000"), [(Token::Integer(1000), 1, 1)]);
+    assert_eq!(tokens("1_000.500_25"), [(Token::Float(1000.50025), 1, 1)]);
+    assert_eq!(tokens("1e1_0"), [(Token::Float(1e10), 1, 1)]);
+    assert_eq!(tokens("0x1_0p0"), [(Token::Float(16.0), 1, 1)]);
+    // a leading '_' makes an identifier
+    assert_eq!(tokens("_1"), [(ident("_1"), 1, 1)]);
+}
+
+#[test]
+fn misplaced_underscores() {
+    for src in ["1__0", "1_", "1._5", "1_.5", "1_e5", "2_000_"] {
+        let e = lex_error(&format!("x = {}", src));
+        assert_eq!(e.message, format!("misplaced '_' in number [{}]", src));
+        assert_eq!((e.pos.line, e.pos.col), (1, 5), "{}", src);
+    }
+}
+
+// reported where it is, in a string or a comment too, and at the very end
+#[test]
+fn null_byte() {
+    for (src, line, col) in [
+        ("println 1 \0 2", 1, 11),
+        ("println \"a\0b\"", 1, 11),
+        ("x\0", 1, 2),
+        ("next\n// a\0", 2, 5),
+        ("next\n/* \0 */", 2, 4),
+        ("\0", 1, 1),
+    ] {
+        let e = lex_error(src);
+        assert_eq!(e.message, "null byte in source", "{:?}", src);
+        assert_eq!((e.pos.line, e.pos.col), (line, col), "{:?}", src);
+    }
+}
+
+// a number with an exponent is a float, the sign of the exponent belongs to the number
+#[test]
+fn scientific_notation() {
+    for (src, value) in [("1e3", 1e3), ("1.5e-3", 1.5e-3), ("2E+2", 200.0), (".5e1", 5.0), ("3e0", 3.0)] {
+        assert_eq!(tokens(src), [(Token::Float(value), 1, 1)], "{}", src);
+    }
+    assert_eq!(tokens("1e3-1"), [(Token::Float(1e3), 1, 1), (Token::Minus, 1, 4), (Token::Integer(1), 1, 5)]);
+    assert_eq!(tokens("2e-1+e1"), [(Token::Float(0.2), 1, 1), (Token::Plus, 1, 5), (ident("e1"), 1, 6)]);
+}
+
+// a malformed float names the bad part and the whole number, positioned at its start
+#[test]
+fn float_format_errors() {
+    for src in ["1e", "1e+", "1.5ex", "1e3.5"] {
+        let e = lex_error(&format!("x = {}", src));
+        assert_eq!((e.message, e.pos.col), (format!("invalid exponent in number [{}]", src), 5));
+    }
+    for src in ["1.2.3", "1..2"] {
+        let e = lex_error(&format!("x = {}", src));
+        assert_eq!((e.message, e.pos.col), (format!("multiple decimal points in number [{}]", src), 5));
+    }
+    assert_eq!(tokens("1."), [(Token::Float(1.0), 1, 1)]);
+    assert_eq!(tokens("1.e5"), [(Token::Float(1e5), 1, 1)]);
+}
+
+// the comments are tokens when kept, with their text and position
+#[test]
+fn kept_comments() {
+    let src = "// head\nlet x = 1 // trailing  \n/* block\n still */ next\n";
+    let stream = Lexer::tokenize_source_with_comments(src, "l.mpl").unwrap();
+    let found: Vec<(Token, usize, usize)> = stream.tokens.iter().map(|t| (t.token.clone(), t.pos.line, t.pos.col)).collect();
+    let comment = |text: &str| Token::Comment(text.to_string());
+    assert_eq!(
+        found,
+        [
+            (comment("// head"), 1, 1),
+            (Token::Let, 2, 1),
+            (ident("x"), 2, 5),
+            (Token::Equal, 2, 7),
+            (Token::Integer(1), 2, 9),
+            (comment("// trailing"), 2, 11),
+            (comment("/* block\n still */"), 3, 1),
+            (Token::Next, 4, 11),
+            (Token::Eof, 5, 1),
+        ]
+    );
+    // the same tokens as without the comments, at the same positions
+    let filtered: Vec<(Token, usize, usize)> = stream.filter_trivia().tokens.into_iter().map(|t| (t.token, t.pos.line, t.pos.col)).collect();
+    let mut without = tokens(src);
+    without.push((Token::Eof, 5, 1));
+    assert_eq!(filtered, without);
+}
+
+// a source in memory: the positions use the virtual name, nothing is read from the disk
+#[test]
+fn tokenize_source() {
+    let stream = Lexer::tokenize_source("import \"missing.mpl\"\nfn main() {\n}\n", "<memory>").unwrap();
+    let found: Vec<(Token, &str, usize, usize)> = stream.tokens.iter().map(|t| (t.token.clone(), t.pos.file_name.as_str(), t.pos.line, t.pos.col)).collect();
+    assert_eq!(found[..2], [(Token::Import, "<memory>", 1, 1), (Token::Str("missing.mpl".to_string()), "<memory>", 1, 8)]);
+    assert_eq!(found.last(), Some(&(Token::Eof, "<memory>", 4, 1)));
+    assert_eq!(found.len(), 9);
+    let Err(e) = Lexer::tokenize_source("x = 1 @", "<memory>") else { panic!("'@' lexed") };
+    assert_eq!((e.pos.file_name.as_str(), e.pos.col), ("<memory>", 7));
+    let empty = Lexer::tokenize_source("", "<memory>").unwrap();
+    assert_eq!(empty.tokens.iter().map(|t| (t.token.clone(), t.pos.line, t.pos.col)).collect::<Vec<_>>(), [(Token::Eof, 1, 1)]);
+}
+
+// the second source starts again at its first line and column, only the settings remain
+#[test]
+fn reset_clears_the_state() {
+    let mut lexer = Lexer::new(String::new());
+    lexer.set_keep_trivia(true);
+    lexer.set_warn_tabs(true);
+    lexer.reset("<a>".to_string(), "let x = 1\n\tlet y = 2\n".to_string());
+    let first = lexer.tokenize_text().unwrap();
+    assert_eq!(first.tokens.last().map(|t| (t.pos.file_name.as_str(), t.pos.line, t.pos.col)), Some(("<a>", 3, 1)));
+    assert_eq!(lexer.warnings().len(), 1);
+    lexer.reset("<b>".to_string(), "z // c\n".to_string());
+    let second = lexer.tokenize_text().unwrap();
+    let found: Vec<(Token, &str, usize, usize)> = second.tokens.iter().map(|t| (t.token.clone(), t.pos.file_name.as_str(), t.pos.line, t.pos.col)).collect();
+    assert_eq!(
+        found,
+        [(ident("z"), "<b>", 1, 1), (Token::Comment("// c".to_string()), "<b>", 1, 3), (Token::Eof, "<b>", 2, 1)]
+    );
+    assert!(lexer.warnings().is_empty());
+    // a source left on an error doesn't affect the next one
+    lexer.reset("<c>".to_string(), "x @".to_string());
+    let Err(e) = lexer.tokenize_text() else { panic!("'@' lexed") };
+    assert_eq!((e.pos.file_name.as_str(), e.pos.col), ("<c>", 3));
+    lexer.reset("<d>".to_string(), "y".to_string());
+    assert_eq!(lexer.tokenize_text().unwrap().tokens.len(), 2);
+}
+
+// a keyword ends where a string starts, no space needed
+#[test]
+fn keyword_touching_a_string() {
+    let s = |text: &str| Token::Str(text.to_string());
+    assert_eq!(tokens("import\"f\""), [(Token::Import, 1, 1), (s("f"), 1, 7)]);
+    assert_eq!(tokens("println\"a\""), [(Token::Println, 1, 1), (s("a"), 1, 8)]);
+    assert_eq!(tokens("from\"f\"import a"), [(Token::From, 1, 1), (s("f"), 1, 5), (Token::Import, 1, 8), (ident("a"), 1, 15)]);
+}
+
+// a lone '_' is the wildcard, with any other identifier char it is an identifier
+#[test]
+fn lone_underscore() {
+    assert_eq!(
+        tokens("_ _a __ _1 a_ _"),
+        [(Token::Underscore, 1, 1), (ident("_a"), 1, 3), (ident("__"), 1, 6), (ident("_1"), 1, 9), (ident("a_"), 1, 12), (Token::Underscore, 1, 15)]
+    );
+    assert_eq!(tokens("(_)"), [(Token::LParen, 1, 1), (Token::Underscore, 1, 2), (Token::RParen, 1, 3)]);
+    assert_eq!(run_str("fn main() {\n    let _x = 1\n    println _x\n}\n", "l.mpl").unwrap(), "1\n");
+    // the wildcard is not a variable name
+    let e = parse_str("fn main() {\n    let _ = 1\n}\n", "l.mpl").unwrap_err();
+    assert_eq!(e.to_string(), "Grammar error : Expected variable name, found '_' at l.mpl line:col -> (2:9)\n");
+}
+
+// digits inside a word belong to the identifier, letters after the digits of a number make
+// an invalid number
+#[test]
+fn mixed_letters_and_digits() {
+    assert_eq!(tokens("abc123"), [(ident("abc123"), 1, 1)]);
+    assert_eq!(tokens("abc12.5"), [(ident("abc12"), 1, 1), (Token::Dot, 1, 6), (Token::Integer(5), 1, 7)]);
+    for (src, message) in [
+        ("12abc", "invalid integer format [12abc]"),
+        ("12abc+1", "invalid integer format [12abc]"),
+        ("1.5abc", "invalid float number format [1.5abc]"),
+    ] {
+        let e = lex_error(src);
+        assert_eq!((e.message.as_str(), e.pos.line, e.pos.col), (message, 1, 1), "{}", src);
+    }
+}
+
+// numbers and words both end at any whitespace, which is dropped, and at an operator, which
+// starts the next token
+#[test]
+fn number_and_word_boundaries() {
+    for (sep, after_number, after_word) in [(" ", (1, 4), (1, 5)), ("\t", (1, 4), (1, 5)), ("\n", (2, 1), (2, 1)), ("\r\n", (2, 1), (2, 1))] {
+        assert_eq!(tokens(&format!("12{}abc", sep)), [(Token::Integer(12), 1, 1), (ident("abc"), after_number.0, after_number.1)], "{:?}", sep);
+        assert_eq!(tokens(&format!("abc{}12", sep)), [(ident("abc"), 1, 1), (Token::Integer(12), after_word.0, after_word.1)], "{:?}", sep);
+    }
+    assert_eq!(tokens("12+3"), [(Token::Integer(12), 1, 1), (Token::Plus, 1, 3), (Token::Integer(3), 1, 4)]);
+    assert_eq!(tokens("a+b"), [(ident("a"), 1, 1), (Token::Plus, 1, 2), (ident("b"), 1, 3)]);
+    assert_eq!(tokens("12==a"), [(Token::Integer(12), 1, 1), (Token::EqEq, 1, 3), (ident("a"), 1, 5)]);
+    assert_eq!(tokens("a==12"), [(ident("a"), 1, 1), (Token::EqEq, 1, 2), (Token::Integer(12), 1, 4)]);
+    assert_eq!(tokens("abc(12)"), [(ident("abc"), 1, 1), (Token::LParen, 1, 4), (Token::Integer(12), 1, 5), (Token::RParen, 1, 7)]);
+    // the sign of a hex exponent belongs to the number, the next one doesn't
+    assert_eq!(tokens("0x1p+2+1"), [(Token::Float(4.0), 1, 1), (Token::Plus, 1, 7), (Token::Integer(1), 1, 8)]);
+    // a char starting a two chars operator ends the word before it, alone it is reported
+    assert_eq!((lex_error("12!").message, lex_error("12!").pos.col), ("Unknown token [!]".to_string(), 3));
+    assert_eq!((lex_error("a!b").message, lex_error("a!b").pos.col), ("Unknown token [!b]".to_string(), 2));
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::{mpl, write_files};
+
+const UNUSED: &[(&str, &str)] = &[
+    ("main.mpl", "import \"util.mpl\"\nfn main() {\n    println 1\n}\n"),
+    ("util.mpl", "fn helper() {\n    println 2\n}\n"),
+];
+
+#[test]
+fn unused_import_warned() {
+    let path = write_files("unused_import", UNUSED);
+    let (ok, out, err) = mpl(&[&path], "");
+    assert!(ok);
+    assert_eq!(out, "1\n");
+    let util = path.replace("main.mpl", "util.mpl");
+    assert_eq!(err, format!("Warning : [unused import {}] at {} (1:1)\n", util, path));
+}
+
+#[test]
+fn called_import_not_warned() {
+    let path = write_files("used_import", &[UNUSED[0], UNUSED[1]]);
+    std::fs::write(&path, "import \"util.mpl\"\nfn main() {\n    call helper()\n}\n").unwrap();
+    let (ok, out, err) = mpl(&["--deny-warnings", &path], "");
+    assert!(ok, "{}", err);
+    assert_eq!((out.as_str(), err.as_str()), ("2\n", ""));
+}
+
+// the program doesn't run when the warnings are errors
+#[test]
+fn deny_warnings_fails() {
+    let path = write_files("deny_warnings", UNUSED);
+    let (ok, out, err) = mpl(&["--deny-warnings", &path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert!(err.ends_with("1 warning(s) treated as errors\n"), "{}", err);
+}
+
+const SHADOWING: &str = "fn main() {\n    let x = 1\n    if true {\n        let x = 2\n        println x\n    }\n    let y = 1\n    for i = 1 to 1 {\n        let y = 3\n    }\n    println x\n}\n";
+
+// a let hiding a variable of an enclosing block, only with --warn-shadow
+#[test]
+fn warn_shadow_option() {
+    let path = write_files("warn_shadow", &[("main.mpl", SHADOWING)]);
+    assert_eq!(mpl(&[&path], ""), (true, "2\n1\n".to_string(), String::new()));
+    let (ok, out, err) = mpl(&["--warn-shadow", &path], "");
+    assert!(ok);
+    assert_eq!(out, "2\n1\n");
+    assert_eq!(
+        err,
+        format!(
+            "Warning : [variable x shadows the one declared at {0} (2:9)] at {0} (4:13)\nWarning : [variable y shadows the one declared at {0} (7:9)] at {0} (9:13)\n",
+            path
+        )
+    );
+    let (ok, out, err) = mpl(&["--warn-shadow", "--deny-warnings", &path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert!(err.ends_with("2 warning(s) treated as errors\n"), "{}", err);
+}
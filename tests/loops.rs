@@ -0,0 +1,81 @@
+use mpl2::{MplError, parse_str, run_str};
+
+fn run(body: &str) -> Result<String, MplError> {
+    run_str(&format!("fn main() {{\n{}\n}}\n", body), "loop.mpl")
+}
+
+#[test]
+fn while_loops() {
+    assert_eq!(run("    while true {\n        break\n    }\n    println 1").unwrap(), "1\n");
+    assert_eq!(run("    let i = 0\n    while i < 3 {\n        print i\n        i = i + 1\n    }\n    println").unwrap(), "012\n");
+    assert_eq!(run("    while false {\n        println 1\n    }").unwrap(), "");
+    let body = "    let i = 0\n    while i < 10 && i != 4 {\n        i = i + 1\n        if i == 2 {\n            next\n        }\n        print i\n    }\n    println";
+    assert_eq!(run(body).unwrap(), "134\n");
+}
+
+// a number is not a condition, strict or not
+#[test]
+fn non_bool_condition() {
+    for (body, message, col) in [
+        ("    while 1 {\n    }", "condition must be a bool, found int", 11),
+        ("    while 1.5 {\n        break\n    }", "condition must be a bool, found float", 11),
+        ("    if 0 {\n    }", "condition must be a bool, found int", 8),
+        ("    while \"true\" {\n    }", "condition must be a bool, found string", 11),
+    ] {
+        match parse_str(&format!("fn main() {{\n{}\n}}\n", body), "loop.mpl") {
+            Err(MplError::Type(e)) => assert_eq!((e.message.as_str(), e.pos.col), (message, col)),
+            other => panic!("{}: {:?}", body, other.map(|p| p.to_sexpr())),
+        }
+    }
+}
+
+fn type_error(body: &str) -> (String, usize, usize) {
+    match parse_str(&format!("fn main() {{\n{}\n}}\n", body), "loop.mpl") {
+        Err(MplError::Type(e)) => (e.message, e.pos.line, e.pos.col),
+        other => panic!("{}: {:?}", body, other.map(|p| p.to_sexpr())),
+    }
+}
+
+#[test]
+fn break_with_and_without_value() {
+    let program = parse_str("fn main() {\n    while true {\n        break\n    }\n    while true {\n        break 5\n    }\n}\n", "loop.mpl").unwrap();
+    assert_eq!(program.to_sexpr(), "(fn main () (while true ((break))) (while true ((break 5))))");
+    let body = "    let i = 0\n    while i < 5 {\n        i = i + 1\n        if i == 3 {\n            break i * 10\n        }\n    }\n    println i";
+    assert_eq!(run(body).unwrap(), "3\n");
+    // the value is evaluated when the loop is left
+    assert!(matches!(run("    while true {\n        break [1][2]\n    }"), Err(MplError::Runtime(_))));
+}
+
+// the break values of a loop have one type, those of a nested loop are checked apart
+#[test]
+fn break_type_per_loop() {
+    assert_eq!(
+        type_error("    while true {\n        if true {\n            break 1\n        }\n        break 1.5\n    }"),
+        ("break values of a loop must have the same type, found int and float".to_string(), 6, 15)
+    );
+    assert_eq!(
+        type_error("    for i = 1 to 3 {\n        break \"a\"\n        break i\n    }"),
+        ("break values of a loop must have the same type, found string and int".to_string(), 4, 15)
+    );
+    let nested = "    for i = 1 to 3 {\n        while true {\n            break \"a\"\n        }\n        break 1\n        break\n    }";
+    assert!(parse_str(&format!("fn main() {{\n{}\n}}\n", nested), "loop.mpl").is_ok());
+}
+
+// without a step the loop counts toward its end, down when the end is below the start
+#[test]
+fn count_down_for_loop() {
+    for (header, out) in [
+        ("i = 10 to 0", "109876543210\n"),
+        ("i = 3 to 1", "321\n"),
+        ("i = 2 to 2", "2\n"),
+        ("i = 3 to 1 step -1", "321\n"),
+        ("i = 10 to 1 step -4", "1062\n"),
+        ("i = 1 to 3 step -1", "\n"),
+    ] {
+        assert_eq!(run(&format!("    for {} {{\n        print i\n    }}\n    println", header)).unwrap(), out, "{}", header);
+    }
+    match run("    for i = 1 to 3 step 0 {\n    }") {
+        Err(MplError::Runtime(e)) => assert_eq!((e.message.as_str(), e.pos.line, e.pos.col), ("for step can't be zero", 2, 5)),
+        other => panic!("{:?}", other),
+    }
+}
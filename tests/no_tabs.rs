@@ -0,0 +1,22 @@
+use mpl2::lexer::Lexer;
+
+const SRC: &str = "fn main() {\n\tprintln \"a\tb\"\n}\n";
+
+#[test]
+fn tab_reported_when_enabled() {
+    let mut lexer = Lexer::new("tabs.mpl".to_string());
+    lexer.set_warn_tabs(true);
+    lexer.reset("tabs.mpl".to_string(), SRC.to_string());
+    lexer.tokenize_text().unwrap();
+    // the tab in the string is kept
+    let found: Vec<(usize, usize)> = lexer.warnings().iter().map(|w| (w.pos.line, w.pos.col)).collect();
+    assert_eq!(found, vec![(2, 1)]);
+}
+
+#[test]
+fn tab_allowed_by_default() {
+    let mut lexer = Lexer::new("tabs.mpl".to_string());
+    lexer.reset("tabs.mpl".to_string(), SRC.to_string());
+    lexer.tokenize_text().unwrap();
+    assert!(lexer.warnings().is_empty());
+}
@@ -0,0 +1,155 @@
+use mpl2::ast::Program;
+use mpl2::lexer::Lexer;
+use mpl2::parser::{ParseError, Parser};
+
+// parse a source with at most max_errors errors collected
+fn parse(src: &str, max_errors: usize) -> Result<Program, ParseError> {
+    let mut parser = Parser::from_stream(Lexer::tokenize_source(src, "e.mpl")?);
+    parser.set_max_errors(max_errors);
+    parser.parse_tokens()
+}
+
+fn error(src: &str, max_errors: usize) -> ParseError {
+    match parse(src, max_errors) {
+        Ok(program) => panic!("{} parsed: {}", src, program.to_sexpr()),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn position_of_each_variant() {
+    let lex = error("fn main() {\n    println 1 @ 2\n}\n", 1);
+    assert!(matches!(lex, ParseError::Lex(_)), "{:?}", lex);
+    assert_eq!((lex.position().line, lex.position().col), (2, 15));
+    let unexpected = error("fn main() {\n    println 1 +\n}\n", 1);
+    assert!(matches!(unexpected, ParseError::Unexpected { .. }), "{:?}", unexpected);
+    assert_eq!((unexpected.position().line, unexpected.position().col), (3, 1));
+    let syntax = error("fn main() {\n    println (1]\n}\n", 1);
+    assert!(matches!(syntax, ParseError::Syntax { .. }), "{:?}", syntax);
+    assert_eq!((syntax.position().line, syntax.position().col), (2, 15));
+    // the first error of several
+    let many = error("fn f() {\n    let = 1\n}\nfn main() {\n    println +\n}\n", 5);
+    assert!(matches!(many, ParseError::Many { .. }), "{:?}", many);
+    assert_eq!((many.position().file_name.as_str(), many.position().line, many.position().col), ("e.mpl", 2, 9));
+}
+
+// a main printing a value in n parentheses
+fn nested(n: usize) -> String {
+    format!("fn main() {{\n    println {}1{}\n}}\n", "(".repeat(n), ")".repeat(n))
+}
+
+fn parse_with_depth(src: &str, max_depth: usize) -> Result<Program, ParseError> {
+    let mut parser = Parser::from_stream(Lexer::tokenize_source(src, "e.mpl")?);
+    parser.set_max_depth(max_depth);
+    parser.parse_tokens()
+}
+
+// the value itself is one level, each parenthesis adds one
+#[test]
+fn nesting_up_to_the_limit() {
+    assert!(parse_with_depth(&nested(3), 4).is_ok());
+    // the default limit fits the stack of a test thread, even in a debug build
+    assert!(parse(&nested(255), 1).is_ok());
+    // the value is only reached through negations, which nest the same way
+    assert!(parse_with_depth("fn main() {\n    println ---1\n}\n", 4).is_ok());
+}
+
+#[test]
+fn nesting_past_the_limit() {
+    let e = parse_with_depth(&nested(4), 4).unwrap_err();
+    assert_eq!(e.to_string(), "Grammar error : expression nesting too deep at e.mpl line:col -> (2:17)\n");
+    let e = parse(&nested(256), 1).unwrap_err();
+    assert_eq!((e.position().line, e.position().col), (2, 269));
+    let arrays = format!("fn main() {{\n    println {}1{}\n}}\n", "[".repeat(256), "]".repeat(256));
+    assert_eq!(error(&arrays, 1).to_string(), "Grammar error : expression nesting too deep at e.mpl line:col -> (2:269)\n");
+    assert!(parse_with_depth("fn main() {\n    println ----1\n}\n", 4).is_err());
+}
+
+// a source with an error in each of n functions, and a valid main
+fn broken_functions(n: usize) -> String {
+    (0..n).map(|i| format!("fn f{}() {{\n    let = {}\n}}\n", i, i)).collect::<String>() + "fn main() {\n}\n"
+}
+
+// lines of the errors collected, and whether the cap was hit
+fn collected(e: &ParseError) -> (Vec<usize>, bool) {
+    match e {
+        ParseError::Many { errors, truncated } => (errors.iter().map(|e| e.position().line).collect(), *truncated),
+        e => (vec![e.position().line], false),
+    }
+}
+
+#[test]
+fn recovery_at_the_next_function() {
+    assert_eq!(collected(&error(&broken_functions(5), 20)), (vec![2, 5, 8, 11, 14], false));
+    // an error in a nested block skips the rest of its function
+    let src = "fn f() {\n    if true {\n        println (\n    }\n    let = 1\n}\nfn g() {\n    println +\n}\nfn main() {\n}\n";
+    assert_eq!(collected(&error(src, 20)), (vec![4, 8], false));
+    // a single error is not wrapped
+    assert!(matches!(error(&broken_functions(1), 20), ParseError::Unexpected { .. }));
+}
+
+#[test]
+fn errors_capped() {
+    assert_eq!(collected(&error(&broken_functions(5), 3)), (vec![2, 5, 8], true));
+    assert_eq!(collected(&error(&broken_functions(5), 5)), (vec![2, 5, 8, 11, 14], false));
+    assert_eq!(collected(&error(&broken_functions(5), 1)), (vec![2], false));
+    let e = error(&broken_functions(3), 2).to_string();
+    assert_eq!(e.lines().last(), Some("\u{2026} and more errors"));
+    assert_eq!(e.lines().count(), 3);
+}
+
+// every token valid where the error is found is listed
+#[test]
+fn expected_tokens_listed() {
+    for (src, expected, found, col) in [
+        ("fn f(a: int b: int) {\n}\nfn main() {\n}\n", vec!["','", "')'"], "b", 13),
+        ("fn main() {\n    call f(1 2)\n}\n", vec!["','", "')'"], "2", 14),
+        ("fn main() {\n    println [1 2]\n}\n", vec!["','", "']'"], "2", 16),
+        ("fn main() {\n    println min(1 2)\n}\n", vec!["','", "')'"], "2", 19),
+    ] {
+        let e = error(src, 1);
+        match &e {
+            ParseError::Unexpected { expected: listed, .. } => assert_eq!(*listed, expected),
+            e => panic!("{:?}", e),
+        }
+        let line = if src.starts_with("fn f") { 1 } else { 2 };
+        assert_eq!(
+            e.to_string(),
+            format!("Grammar error : Expected one of {}, found '{}' at e.mpl line:col -> ({}:{})\n", expected.join(", "), found, line, col)
+        );
+    }
+    // a single one is named alone
+    assert_eq!(
+        error("fn main() {\n    for i 1 to 2 {\n    }\n}\n", 1).to_string(),
+        "Grammar error : Expected '=', found '1' at e.mpl line:col -> (2:11)\n"
+    );
+}
+
+// a source without any function is told apart from one missing main, at the end of the file
+#[test]
+fn no_declarations() {
+    for (src, line) in [("", 1), ("\n\n", 3), ("// only a comment\n\n/* and a block */\n", 4)] {
+        assert_eq!(
+            error(src, 1).to_string(),
+            format!("Grammar error : source file contains no declarations at e.mpl line:col -> ({}:1)\n", line)
+        );
+    }
+    assert_eq!(error("fn f() {\n}\n", 1).to_string(), "Grammar error : main function not found at e.mpl line:col -> (3:1)\n");
+}
+
+// a closing bracket where another one is expected suggests the expected one
+#[test]
+fn closing_bracket_suggestion() {
+    for (statement, message, col) in [
+        ("call f(1]", "found ']', did you mean ')'?", 13),
+        ("call f(1}", "found '}', did you mean ')'?", 13),
+        ("println (1 + 2]", "found ']', did you mean ')'?", 19),
+        ("println [1, 2)", "found ')', did you mean ']'?", 18),
+    ] {
+        let e = error(&format!("fn f(a: int) {{\n}}\nfn main() {{\n    {}\n}}\n", statement), 1);
+        assert_eq!(e.to_string(), format!("Grammar error : {} at e.mpl line:col -> (4:{})\n", message, col), "{}", statement);
+    }
+    // any other token is reported with the expected ones
+    let e = error("fn main() {\n    println [1 2]\n}\n", 1);
+    assert_eq!(e.to_string(), "Grammar error : Expected one of ',', ']', found '2' at e.mpl line:col -> (2:16)\n");
+}
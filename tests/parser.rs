@@ -0,0 +1,240 @@
+mod common;
+
+use mpl2::ast::{ExprKind, NodeId, NodeMap, StmtKind};
+use mpl2::lexer::Lexer;
+use mpl2::parser::Parser;
+use mpl2::{MplError, parse_str, run_str};
+
+use common::write_files;
+
+// S-expression of a program whose main has the given body
+fn sexpr(body: &str) -> String {
+    parse_str(&format!("fn main() {{\n{}\n}}\n", body), "p.mpl").unwrap().to_sexpr()
+}
+
+// the two spellings only differ by their positions, which the S-expression leaves out
+#[test]
+fn elif_same_as_else_if() {
+    let elif = sexpr("    let x = 2\n    if x == 1 {\n        println 1\n    } elif x == 2 {\n        println 2\n    } else {\n        println 3\n    }");
+    let else_if = sexpr("    let x = 2\n    if x == 1 {\n        println 1\n    } else if x == 2 {\n        println 2\n    } else {\n        println 3\n    }");
+    assert_eq!(elif, else_if);
+    assert_eq!(elif, "(fn main () (let x 2) (if (== x 1) ((println 1)) ((if (== x 2) ((println 2)) ((println 3))))))");
+}
+
+#[test]
+fn empty_function_bodies() {
+    let program = parse_str("fn f() {}\nfn g(a: int) {\n}\nfn main() {\n    call f()\n    call g(1)\n}\n", "p.mpl").unwrap();
+    assert_eq!(program.to_sexpr(), "(fn f ())\n(fn g ((a int)))\n(fn main () (call f) (call g 1))");
+    assert_eq!(run_str("fn main() {}\n", "p.mpl").unwrap(), "");
+}
+
+// the nodes compare their content and their positions
+#[test]
+fn clone_equals_original() {
+    let src = "fn f(a: int) {\n    println a\n}\nfn main() {\n    let x = [1, 2]\n    if x[0] < 2 {\n        call f(x[1])\n    }\n}\n";
+    let program = parse_str(src, "p.mpl").unwrap();
+    let copy = program.clone();
+    assert_eq!(copy, program);
+    assert_eq!(copy.functions[1].body, program.functions[1].body);
+    assert_ne!(parse_str(&src.replace("[1, 2]", "[1, 3]"), "p.mpl").unwrap(), program);
+    assert_ne!(parse_str(&src.replace("    let", "  let"), "p.mpl").unwrap(), program);
+}
+
+#[test]
+fn function_lookup() {
+    let program = parse_str("fn add(a: int, b: int) {\n    println a + b\n}\nfn nop() {}\nfn main() {\n}\n", "p.mpl").unwrap();
+    let names: Vec<&str> = program.functions().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["add", "nop", "main"]);
+    assert_eq!(program.function("add").map(|f| f.param_count()), Some(2));
+    assert_eq!(program.function("nop").map(|f| f.param_count()), Some(0));
+    assert_eq!(program.function("main").map(|f| (f.pos.line, f.pos.col)), Some((5, 1)));
+    assert!(program.function("missing").is_none());
+}
+
+// the grammar error of a main with the given body
+fn parse_error(body: &str) -> String {
+    match parse_str(&format!("fn main() {{\n{}\n}}\n", body), "p.mpl") {
+        Err(MplError::Parse(e)) => e.to_string(),
+        other => panic!("{}: {:?}", body, other.map(|p| p.to_sexpr())),
+    }
+}
+
+#[test]
+fn multi_variable_let() {
+    assert_eq!(sexpr("    let a, b, c = 0"), "(fn main () (let a 0) (let b 0) (let c 0))");
+    assert_eq!(sexpr("    let a = 1, b = 2.5"), "(fn main () (let a 1) (let b 2.5))");
+    assert_eq!(sexpr("    let a, b = 1, 2"), "(fn main () (let a 1) (let b 2))");
+    assert_eq!(run_str("fn main() {\n    let a, b = 1, a + 1\n    println a, b\n}\n", "p.mpl").unwrap(), "1 2\n");
+}
+
+#[test]
+fn multi_variable_let_count_mismatch() {
+    assert_eq!(parse_error("    let a, b = 1, 2, 3"), "Grammar error : 2 variables declared but 3 values given at p.mpl line:col -> (2:5)\n");
+    assert_eq!(parse_error("    let a, b, c = 1, 2"), "Grammar error : 3 variables declared but 2 values given at p.mpl line:col -> (2:5)\n");
+    assert_eq!(parse_error("    let a = 1, b"), "Grammar error : Expected '=', found '}' at p.mpl line:col -> (3:1)\n");
+}
+
+#[test]
+fn break_and_next_outside_a_loop() {
+    assert_eq!(parse_error("    break"), "Grammar error : break outside of loop at p.mpl line:col -> (2:5)\n");
+    assert_eq!(parse_error("    next"), "Grammar error : next outside of loop at p.mpl line:col -> (2:5)\n");
+    assert_eq!(parse_error("    if true {\n        break\n    }"), "Grammar error : break outside of loop at p.mpl line:col -> (3:9)\n");
+    // a function declared in a loop body is outside of it
+    assert_eq!(
+        parse_error("    while true {\n        fn f() {\n            next\n        }\n        break\n    }"),
+        "Grammar error : next outside of loop at p.mpl line:col -> (4:13)\n"
+    );
+    assert_eq!(sexpr("    while true {\n        if true {\n            next\n        }\n        break\n    }"), "(fn main () (while true ((if true ((next))) (break))))");
+}
+
+const SEXPR_SRC: &str = r#"fn sum(a: [int], n: int) {
+    let total = 0
+    for i = 0 to n - 1 step 1 {
+        total = total + a[i]
+    }
+    print total
+    println
+}
+fn main() {
+    local x: float = 1.5
+    let s = "hi"
+    if -x < 2 && (x > 1) || true {
+        call sum([1, 2], 2)
+    } else {
+        eprintln to_str(x) == s
+        eprintln len(s), x : 2
+    }
+    while true {
+        break x as int
+    }
+}
+"#;
+
+// every kind of statement, and the expressions in prefix form
+#[test]
+fn sexpr_rendering() {
+    let program = parse_str(SEXPR_SRC, "p.mpl").unwrap();
+    let functions: Vec<String> = program.functions().map(|f| f.to_sexpr()).collect();
+    assert_eq!(functions.join("\n"), program.to_sexpr());
+    assert_eq!(
+        program.to_sexpr(),
+        concat!(
+            "(fn sum ((a [int]) (n int)) (let total 0) (for i 0 (- n 1) 1 ((= total (+ total (index a i))))) (print total) (println))\n",
+            "(fn main () (local (x float) 1.5) (let s \"hi\") (if (|| (&& (< (- x) 2) (> x 1)) true) ((call sum (array 1 2) 2)) ((eprintln (== (to_str x) s)) (eprintln (len s) x (precision 2)))) (while true ((break (as x int)))))"
+        ),
+    );
+    assert_eq!(sexpr("    println 1 + 2 * 3, (1 + 2) * 3, -1"), "(fn main () (println (+ 1 (* 2 3)) (* (+ 1 2) 3) (- 1)))");
+}
+
+// the tokens after the parse: imports spliced, comments dropped, eof last
+#[test]
+fn parser_tokens() {
+    let path = write_files("parser_tokens", &[("main.mpl", "import \"u.mpl\"\n// c\nfn main() {\n    call u()\n}\n"), ("u.mpl", "fn u() {\n}\n")]);
+    let mut parser = Parser::new();
+    parser.parse(path).unwrap();
+    let tokens: Vec<String> = parser.tokens().iter().map(|t| t.token.to_string()).collect();
+    assert_eq!(tokens, ["fn", "u", "(", ")", "{", "}", "fn", "main", "(", ")", "{", "call", "u", "(", ")", "}", "end of file"]);
+    let mut parser = Parser::from_stream(Lexer::tokenize_source("fn main() {\n}\n", "p.mpl").unwrap());
+    assert_eq!(parser.tokens().len(), 7);
+    parser.parse_tokens().unwrap();
+    assert_eq!(parser.tokens().len(), 7);
+}
+
+// a stream lexed once is parsed as is: its comments are dropped and an end of file is added
+// when missing
+#[test]
+fn parse_a_lexed_stream() {
+    let src = "// c\nfn main() {\n    println 1 /* two */ + 2\n}\n";
+    let stream = Lexer::tokenize_source_with_comments(src, "<memory>").unwrap();
+    let program = stream.into_parser().parse_tokens().unwrap();
+    assert_eq!(program.to_sexpr(), "(fn main () (println (+ 1 2)))");
+    assert_eq!(program.functions[0].pos.file_name, "<memory>");
+    let mut stream = Lexer::tokenize_source(src, "<memory>").unwrap();
+    stream.tokens.pop();
+    let mut parser = Parser::from_stream(stream);
+    assert_eq!(parser.tokens().last().map(|t| (t.token.to_string(), t.pos.line)), Some(("end of file".to_string(), 4)));
+    assert_eq!(parser.parse_tokens().unwrap().to_sexpr(), program.to_sexpr());
+    let e = Lexer::tokenize_source("fn main() {\n", "<memory>").unwrap().into_parser().parse_tokens().unwrap_err();
+    assert!(e.to_string().starts_with("Grammar error : "), "{}", e);
+}
+
+// to and step only belong to the header of a for, next is a statement
+#[test]
+fn misplaced_loop_keywords() {
+    for (body, error) in [
+        ("    println to", "'to' is only valid inside a for loop at p.mpl line:col -> (2:13)"),
+        ("    let x = step", "'step' is only valid inside a for loop at p.mpl line:col -> (2:13)"),
+        ("    step 1", "'step' is only valid inside a for loop at p.mpl line:col -> (2:5)"),
+        ("    for i = 1 to 3 {\n        println step\n    }", "'step' is only valid inside a for loop at p.mpl line:col -> (3:17)"),
+        ("    for i = 1 to step {\n    }", "'step' is only valid inside a for loop at p.mpl line:col -> (2:18)"),
+        ("    println next", "next outside of loop at p.mpl line:col -> (2:13)"),
+        ("    for i = 1 step 2 {\n    }", "Expected 'to', found 'step' at p.mpl line:col -> (2:15)"),
+        ("    for i = 1 to 2 to 3 {\n    }", "Expected '{', found 'to' at p.mpl line:col -> (2:20)"),
+        ("    let to = 1", "Expected variable name, found 'to' at p.mpl line:col -> (2:9)"),
+    ] {
+        assert_eq!(parse_error(body), format!("Grammar error : {}\n", error), "{}", body);
+    }
+}
+
+// adjacent string literals are one string, even across lines; any other token keeps them apart
+#[test]
+fn adjacent_strings_merged() {
+    assert_eq!(sexpr("    println \"foo\" \"bar\""), "(fn main () (println \"foobar\"))");
+    assert_eq!(sexpr("    println \"a\"\n        \"b\" \"c\", 1"), "(fn main () (println \"abc\" 1))");
+    assert_eq!(sexpr("    println \"a\", \"b\""), "(fn main () (println \"a\" \"b\"))");
+    assert_eq!(sexpr("    println \"a\" + \"b\""), "(fn main () (println (+ \"a\" \"b\")))");
+    assert_eq!(run_str("fn main() {\n    println \"foo\" \"bar\"\n}\n", "p.mpl").unwrap(), "foobar\n");
+}
+
+// the operators of a level associate to the left, a tighter level binds first on either side
+#[test]
+fn precedence_climbing() {
+    for (expr, tree) in [
+        ("1 - 2 - 3", "(- (- 1 2) 3)"),
+        ("8 / 4 / 2", "(/ (/ 8 4) 2)"),
+        ("1 - 2 + 3", "(+ (- 1 2) 3)"),
+        ("1 * 2 + 3 * 4", "(+ (* 1 2) (* 3 4))"),
+        ("1 + 2 < 3 * 4", "(< (+ 1 2) (* 3 4))"),
+        ("1 < 2 == true", "(== (< 1 2) true)"),
+        ("true || false && 1 == 2", "(|| true (&& false (== 1 2)))"),
+        ("true && false || true && true", "(|| (&& true false) (&& true true))"),
+        ("-1 * 2 - (3 - 4)", "(- (* (- 1) 2) (- 3 4))"),
+    ] {
+        assert_eq!(sexpr(&format!("    println {}", expr)), format!("(fn main () (println {}))", tree), "{}", expr);
+    }
+    assert_eq!(run_str("fn main() {\n    println 10 - 4 - 3, 2 + 3 * 4 - 1\n}\n", "p.mpl").unwrap(), "3 13\n");
+}
+
+// the statements and expressions are numbered in source order across the functions, a
+// statement before its expressions, and the same source gives the same ids
+#[test]
+fn node_ids() {
+    let src = "fn f() {\n    println 1 + 2\n}\nfn main() {\n    let x = 3\n    call f()\n}\n";
+    let program = parse_str(src, "p.mpl").unwrap();
+    assert_eq!(program.node_count, 7);
+    let mut ids = Vec::new();
+    for function in program.functions() {
+        for stmt in &function.body {
+            ids.push(stmt.id);
+            match &stmt.kind {
+                StmtKind::Print { args, .. } => {
+                    let ExprKind::Binary { lhs, rhs, .. } = &args[0].kind else { panic!("{}", args[0].to_sexpr()) };
+                    ids.extend([args[0].id, lhs.id, rhs.id]);
+                }
+                StmtKind::Let { value: Some(value), .. } => ids.push(value.id),
+                StmtKind::Call { args, .. } => assert!(args.is_empty()),
+                _ => panic!("{}", stmt.to_sexpr()),
+            }
+        }
+    }
+    assert_eq!(ids, (0..7).map(NodeId).collect::<Vec<_>>());
+    assert_eq!(parse_str(src, "p.mpl").unwrap(), program);
+    // a side table keeps a value for some of the nodes
+    let mut names = NodeMap::new();
+    names.insert(NodeId(4), "let");
+    names.insert(NodeId(1), "+");
+    assert_eq!((names.get(NodeId(4)), names.get(NodeId(1))), (Some(&"let"), Some(&"+")));
+    assert_eq!((names.get(NodeId(0)), names.get(NodeId(9))), (None, None));
+    names.insert(NodeId(4), "let x");
+    assert_eq!(names.get(NodeId(4)), Some(&"let x"));
+}
@@ -0,0 +1,33 @@
+use mpl2::lexer::Position;
+
+fn at(pos: &Position) -> (usize, usize) {
+    (pos.line, pos.col)
+}
+
+#[test]
+fn bump_col_and_newline() {
+    let mut pos = Position::new("p.mpl".to_string());
+    assert_eq!(at(&pos), (1, 1));
+    pos.bump_col(1);
+    pos.bump_col(3);
+    assert_eq!(at(&pos), (1, 5));
+    pos.bump_col(0);
+    assert_eq!(at(&pos), (1, 5));
+    pos.newline();
+    assert_eq!(at(&pos), (2, 1));
+    pos.newline();
+    pos.bump_col(2);
+    assert_eq!(at(&pos), (3, 3));
+    assert_eq!(pos.file_name, "p.mpl");
+}
+
+// the first char of a file, the default one has no file
+#[test]
+fn start_and_default() {
+    let start = Position::start("s.mpl");
+    assert_eq!((start.file_name.as_str(), at(&start)), ("s.mpl", (1, 1)));
+    assert_eq!(Position::start(String::from("s.mpl")), start);
+    assert_eq!(Position::new("s.mpl".to_string()), start);
+    let default = Position::default();
+    assert_eq!((default.file_name.as_str(), at(&default)), ("", (1, 1)));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use mpl2::eval::Interpreter;
+use mpl2::{MplError, parse_str, run_str};
+
+use common::{mpl, write_files};
+
+fn run(body: &str) -> Result<String, MplError> {
+    run_str(&format!("fn main() {{\n{}\n}}\n", body), "print.mpl")
+}
+
+// the grammar error of a main with the given body
+fn parse_error(body: &str) -> String {
+    match run(body) {
+        Err(MplError::Parse(e)) => e.to_string(),
+        other => panic!("{}: {:?}", body, other),
+    }
+}
+
+// the precision after the values applies to all the floats, the ints print as they are
+#[test]
+fn precision_after_the_values() {
+    assert_eq!(run("    println 3.14159, 2, 1.0 / 3 : 2").unwrap(), "3.14 2 0.33\n");
+    assert_eq!(run("    println 3 : 2").unwrap(), "3\n");
+    assert_eq!(run("    println 3 as float : 2").unwrap(), "3.00\n");
+    assert_eq!(run("    print 2.5 : 0\n    println").unwrap(), "2\n");
+    assert_eq!(run("    println 2.5").unwrap(), "2.5\n");
+}
+
+#[test]
+fn invalid_precision_rejected() {
+    assert_eq!(
+        parse_error("    println 1.5 : -1"),
+        "Grammar error : format precision can't be negative at print.mpl line:col -> (2:19)\n"
+    );
+    assert_eq!(
+        parse_error("    println 1.5 : x"),
+        "Grammar error : Expected format precision, found 'x' at print.mpl line:col -> (2:19)\n"
+    );
+}
+
+// the stdout and stderr of a main with the given body
+fn run_both(body: &str) -> (String, String) {
+    let program = parse_str(&format!("fn main() {{\n{}\n}}\n", body), "print.mpl").unwrap();
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    Interpreter::with_output(Box::new(&mut out), Box::new(&mut err)).run(&program).unwrap();
+    (String::from_utf8(out).unwrap(), String::from_utf8(err).unwrap())
+}
+
+#[test]
+fn eprint_goes_to_stderr() {
+    let (out, err) = run_both("    println 1\n    eprint \"warn\", 2\n    eprintln \" done\"\n    print 3\n    eprintln 1.5 : 2");
+    assert_eq!(out, "1\n3");
+    assert_eq!(err, "warn 2 done\n1.50\n");
+    assert_eq!(
+        parse_str("fn main() {\n    eprintln 1\n}\n", "print.mpl").unwrap().to_sexpr(),
+        "(fn main () (eprintln 1))"
+    );
+}
+
+#[test]
+fn eprint_from_the_command_line() {
+    let path = write_files("eprint", &[("main.mpl", "fn main() {\n    println \"out\"\n    eprintln \"err\"\n}\n")]);
+    assert_eq!(mpl(&[&path], ""), (true, "out\n".to_string(), "err\n".to_string()));
+}
+
+// println alone prints a blank line, print needs something to print
+#[test]
+fn bare_println() {
+    assert_eq!(run("    println\n    print 1\n    println\n    println 2").unwrap(), "\n1\n2\n");
+    let (out, err) = run_both("    eprintln\n    println");
+    assert_eq!((out.as_str(), err.as_str()), ("\n", "\n"));
+}
+
+#[test]
+fn print_without_value() {
+    assert_eq!(parse_error("    print"), "Grammar error : print expects a value at print.mpl line:col -> (2:5)\n");
+    assert_eq!(parse_error("    eprint"), "Grammar error : eprint expects a value at print.mpl line:col -> (2:5)\n");
+    assert_eq!(parse_error("    print : 2"), "Grammar error : print expects a value at print.mpl line:col -> (2:5)\n");
+}
+
+// the values are separated by a space, print doesn't end the line
+#[test]
+fn several_values() {
+    assert_eq!(run("    println 1, \"a\", 2.5, true, [1, 2]").unwrap(), "1 a 2.5 true [1, 2]\n");
+    assert_eq!(run("    print 1, 2\n    println 3").unwrap(), "1 23\n");
+    assert_eq!(
+        parse_str("fn main() {\n    println 1, \"a\"\n}\n", "print.mpl").unwrap().to_sexpr(),
+        "(fn main () (println 1 \"a\"))"
+    );
+    for body in ["    println 1,", "    print 1, , 2"] {
+        let statement = body.split_whitespace().next().unwrap();
+        assert_eq!(
+            parse_error(body),
+            format!("Grammar error : trailing ',' after the values of {} at print.mpl line:col -> (2:{})\n", statement, body.find(',').unwrap() + 1)
+        );
+    }
+}
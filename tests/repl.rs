@@ -0,0 +1,21 @@
+mod common;
+
+use common::mpl;
+
+#[test]
+fn variables_and_functions_persist_across_lines() {
+    let input = "let x = 2\nfn twice(n: int) { println n * 2 }\nx = x + 1\ncall twice(x)\nprintln x\n";
+    let (ok, out, err) = mpl(&["--repl"], input);
+    assert!(ok, "{}", err);
+    assert_eq!(out, "6\n3\n");
+}
+
+#[test]
+fn line_with_an_error_declares_nothing() {
+    let input = "let y = 1 + \"a\"\nprintln y\nlet y = 4\nprintln y\n";
+    let (ok, out, err) = mpl(&["--repl"], input);
+    assert!(ok);
+    assert_eq!(out, "4\n");
+    assert!(err.contains("Type error"), "{}", err);
+    assert!(err.contains("unknown variable y"), "{}", err);
+}
@@ -0,0 +1,62 @@
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+use mpl2::import::{ImportResolver, MemoryResolver};
+use mpl2::lexer::Lexer;
+
+fn memory(files: &[(&str, &str)]) -> Rc<dyn ImportResolver> {
+    let mut resolver = MemoryResolver::new();
+    for (path, src) in files {
+        resolver.insert(*path, *src);
+    }
+    Rc::new(resolver)
+}
+
+#[test]
+fn memory_resolver_through_the_trait() {
+    let resolver = memory(&[("a.mpl", "fn main() {\n}\n")]);
+    assert_eq!(resolver.read("a.mpl").unwrap(), "fn main() {\n}\n");
+    assert_eq!(resolver.read("b.mpl").unwrap_err().kind(), io::ErrorKind::NotFound);
+    assert!(resolver.is_file("a.mpl"));
+    assert!(!resolver.is_file("b.mpl"));
+    assert!(!resolver.is_dir("a.mpl"));
+}
+
+#[test]
+fn imports_read_from_memory() {
+    let resolver = memory(&[
+        ("a.mpl", "import \"b.mpl\"\nfn main() {\n    call b()\n}\n"),
+        ("b.mpl", "import \"c.mpl\"\nfn b() {\n    call c()\n}\n"),
+        ("c.mpl", "fn c() {\n}\n"),
+    ]);
+    let mut lexer = Lexer::with_resolver("a.mpl".to_string(), resolver);
+    let tokens = lexer.tokenize().unwrap();
+    let files: Vec<&str> = tokens.iter().map(|t| t.pos.file_name.as_str()).collect();
+    assert!(files.contains(&"b.mpl") && files.contains(&"c.mpl"));
+    let missing = Lexer::with_resolver("x.mpl".to_string(), memory(&[])).tokenize().unwrap_err();
+    assert_eq!(missing.message, "File not found x.mpl");
+}
+
+// the lexer only goes through the resolver to read the files
+struct Logged {
+    inner: Rc<dyn ImportResolver>,
+    reads: RefCell<Vec<String>>,
+}
+
+impl ImportResolver for Logged {
+    fn read(&self, path: &str) -> Result<String, io::Error> {
+        self.reads.borrow_mut().push(path.to_string());
+        self.inner.read(path)
+    }
+}
+
+#[test]
+fn custom_resolver() {
+    let logged = Rc::new(Logged {
+        inner: memory(&[("a.mpl", "import \"b.mpl\"\nfn main() {\n}\n"), ("b.mpl", "fn b() {\n}\n")]),
+        reads: RefCell::new(Vec::new()),
+    });
+    Lexer::with_resolver("a.mpl".to_string(), logged.clone()).tokenize().unwrap();
+    assert_eq!(*logged.reads.borrow(), ["a.mpl", "b.mpl"]);
+}
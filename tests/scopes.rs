@@ -0,0 +1,123 @@
+use mpl2::lexer::Lexer;
+use mpl2::parser::Parser;
+use mpl2::resolve::Resolver;
+use mpl2::{MplError, parse_str, run_str};
+
+fn run(body: &str) -> Result<String, MplError> {
+    run_str(&format!("fn main() {{\n{}\n}}\n", body), "scope.mpl")
+}
+
+// message of the resolve error of a program whose main has the given body
+fn resolve_error(body: &str) -> String {
+    match parse_str(&format!("fn main() {{\n{}\n}}\n", body), "scope.mpl") {
+        Err(MplError::Resolve(e)) => e.message,
+        other => panic!("{}: {:?}", body, other.map(|p| p.to_sexpr())),
+    }
+}
+
+#[test]
+fn local_declared_in_every_branch_usable_after_if() {
+    let body = "    let c = true\n    if c {\n        local x = 1\n    } else {\n        local x = 2\n    }\n    println x";
+    assert_eq!(run(body).unwrap(), "1\n");
+}
+
+#[test]
+fn local_visible_in_nested_blocks_and_redeclaring_assigns() {
+    let body = "    local x = 1\n    if true {\n        local x = x + 1\n    }\n    println x";
+    assert_eq!(run(body).unwrap(), "2\n");
+}
+
+#[test]
+fn let_not_visible_after_its_block() {
+    assert_eq!(resolve_error("    if true {\n        let x = 1\n    }\n    println x"), "unknown variable x");
+    assert_eq!(resolve_error("    while false {\n        let x = 1\n    }\n    println x"), "unknown variable x");
+}
+
+#[test]
+fn local_in_one_branch_possibly_uninitialized() {
+    let message = "use of possibly-uninitialized local x, not declared on every path to here";
+    assert_eq!(resolve_error("    if true {\n        local x = 1\n    }\n    println x"), message);
+    assert_eq!(resolve_error("    while false {\n        local x = 1\n    }\n    println x"), message);
+}
+
+#[test]
+fn let_without_value_possibly_uninitialized() {
+    assert_eq!(
+        resolve_error("    let x: int\n    if true {\n        x = 1\n    }\n    println x"),
+        "use of possibly-uninitialized variable x"
+    );
+}
+
+// the loop variable is a new binding of the body, hiding a variable of the same name
+#[test]
+fn for_variable_scoped_to_the_loop() {
+    assert_eq!(resolve_error("    for i = 1 to 2 {\n        println i\n    }\n    println i"), "unknown variable i");
+    let body = "    let i = 10\n    for i = 1 to 2 {\n        print i\n    }\n    println i";
+    assert_eq!(run(body).unwrap(), "1210\n");
+}
+
+#[test]
+fn let_without_value_assigned_later() {
+    assert_eq!(run("    let x: int\n    x = 1\n    println x").unwrap(), "1\n");
+    let body = "    let x: float\n    if false {\n        x = 1\n    } else {\n        x = 2.5\n    }\n    println x";
+    assert_eq!(run(body).unwrap(), "2.5\n");
+}
+
+#[test]
+fn definite_assignment_errors() {
+    let message = "use of possibly-uninitialized variable x";
+    assert_eq!(resolve_error("    let x: int\n    println x"), message);
+    assert_eq!(resolve_error("    let x: int\n    x = x + 1"), message);
+    assert_eq!(resolve_error("    let x: int\n    if false {\n        x = 1\n    } else {\n        println 2\n    }\n    println x"), message);
+    assert_eq!(resolve_error("    let x: int\n    while true {\n        x = 1\n        break\n    }\n    println x"), message);
+    match parse_str("fn main() {\n    let x\n}\n", "scope.mpl") {
+        Err(MplError::Parse(e)) => assert_eq!(
+            e.to_string(),
+            "Grammar error : variable x declared without a value needs a type at scope.mpl line:col -> (2:9)\n"
+        ),
+        other => panic!("{:?}", other.map(|p| p.to_sexpr())),
+    }
+}
+
+const NESTED_FNS: &str = "    fn double(x: int) {
+        fn show(y: int) {
+            println y
+        }
+        call show(x * 2)
+    }
+    call double(2)
+    if true {
+        fn inner() {
+            println \"inner\"
+        }
+        call inner()
+    }";
+
+// a function declared in a block is called in that block, it doesn't see the variables
+// around it
+#[test]
+fn nested_functions() {
+    let program = parse_str(&format!("fn main() {{\n{}\n}}\n", NESTED_FNS), "scope.mpl").unwrap();
+    assert_eq!(
+        program.to_sexpr(),
+        "(fn main () (fn double ((x int)) (fn show ((y int)) (println y)) (call show (* x 2))) (call double 2) (if true ((fn inner () (println \"inner\")) (call inner))))"
+    );
+    assert_eq!(run(NESTED_FNS).unwrap(), "4\ninner\n");
+    assert_eq!(resolve_error("    if true {\n        fn inner() {\n        }\n    }\n    call inner()"), "unknown function inner");
+    assert_eq!(resolve_error("    let v = 1\n    fn f() {\n        println v\n    }\n    call f()"), "unknown variable v");
+}
+
+#[test]
+fn repl_lines_with_the_same_positions_keep_their_declarations_apart() {
+    let mut parser = Parser::new();
+    let mut resolver = Resolver::new();
+    let mut line = |src: &str| {
+        let block = parser.parse_statements(Lexer::tokenize_source(src, "<repl>").unwrap().tokens).unwrap();
+        resolver.resolve_statements(&block).map_err(|e| e.message)
+    };
+    // both declarations are at 1:1, only the first one is assigned
+    assert_eq!(line("let a = 1"), Ok(()));
+    assert_eq!(line("let b: int"), Ok(()));
+    assert_eq!(line("println b"), Err("use of possibly-uninitialized variable b".to_string()));
+    assert_eq!(line("println a"), Ok(()));
+}
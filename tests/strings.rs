@@ -0,0 +1,38 @@
+use mpl2::lexer::Lexer;
+use mpl2::token::Token;
+
+fn lex_string(src: &str) -> Result<String, String> {
+    let stream = Lexer::tokenize_source(src, "s.mpl").map_err(|e| e.message)?;
+    match &stream.tokens[0].token {
+        Token::Str(s) => Ok(s.clone()),
+        token => Err(format!("not a string: {}", token)),
+    }
+}
+
+#[test]
+fn escaped_quotes() {
+    assert_eq!(lex_string(r#""he said \"hi\"""#), Ok(r#"he said "hi""#.to_string()));
+    assert_eq!(lex_string(r#"'it\'s'"#), Ok("it's".to_string()));
+}
+
+#[test]
+fn escaped_backslash() {
+    assert_eq!(lex_string(r#""a\\b""#), Ok(r"a\b".to_string()));
+    // a string ending with a backslash
+    assert_eq!(lex_string(r#""C:\\""#), Ok(r"C:\".to_string()));
+}
+
+#[test]
+fn unclosed_by_a_trailing_backslash() {
+    assert_eq!(lex_string("\"abc\\\"\n"), Err("Unclosed string".to_string()));
+    assert_eq!(lex_string("\"abc\\\n\""), Err("Unclosed string".to_string()));
+}
+
+#[test]
+fn unknown_escape() {
+    let Err(e) = Lexer::tokenize_source(r#"  "a\qb""#, "s.mpl") else {
+        panic!("unknown escape accepted");
+    };
+    assert_eq!(e.message, r"unknown escape '\q' in string");
+    assert_eq!((e.pos.line, e.pos.col), (1, 5));
+}
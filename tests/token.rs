@@ -0,0 +1,84 @@
+use mpl2::token::{Token, TokenKind};
+
+#[test]
+fn classification() {
+    let keywords = [Token::Fn, Token::Let, Token::Local, Token::While, Token::Break, Token::As, Token::IntType, Token::Println];
+    let operators = [Token::Plus, Token::Slash, Token::Equal, Token::EqEq, Token::Le, Token::AndAnd, Token::OrOr];
+    let literals = [Token::Integer(1), Token::Float(1.5), Token::Str("s".to_string()), Token::True, Token::False];
+    let others = [Token::Ident("x".to_string()), Token::LParen, Token::Comma, Token::Colon, Token::Dot, Token::Underscore, Token::Comment("// c".to_string()), Token::Eof];
+    for t in &keywords {
+        assert_eq!((t.is_keyword(), t.is_operator(), t.is_literal()), (true, false, false), "{:?}", t);
+    }
+    for t in &operators {
+        assert_eq!((t.is_keyword(), t.is_operator(), t.is_literal()), (false, true, false), "{:?}", t);
+    }
+    for t in &literals {
+        assert_eq!((t.is_keyword(), t.is_operator(), t.is_literal()), (false, false, true), "{:?}", t);
+    }
+    for t in &others {
+        assert_eq!((t.is_keyword(), t.is_operator(), t.is_literal()), (false, false, false), "{:?}", t);
+    }
+}
+
+// a token is displayed as written in the source
+#[test]
+fn display() {
+    for (token, shown) in [
+        (Token::Fn, "fn"),
+        (Token::Println, "println"),
+        (Token::Le, "<="),
+        (Token::AndAnd, "&&"),
+        (Token::LBrace, "{"),
+        (Token::Ident("total".to_string()), "total"),
+        (Token::Str("a \"b\"".to_string()), "\"a \\\"b\\\"\""),
+        (Token::Integer(-3), "-3"),
+        (Token::Float(2.0), "2.0"),
+        (Token::Float(0.25), "0.25"),
+        (Token::Float(1e20), "100000000000000000000.0"),
+        (Token::Comment("// c".to_string()), "// c"),
+        (Token::ImportStart("u.mpl".to_string()), "start of import u.mpl"),
+        (Token::ImportEnd("u.mpl".to_string()), "end of import u.mpl"),
+        (Token::Underscore, "_"),
+        (Token::Eof, "end of file"),
+    ] {
+        assert_eq!(token.to_string(), shown, "{:?}", token);
+    }
+}
+
+#[test]
+fn same_kind_ignores_the_payload() {
+    let ident = |name: &str| Token::Ident(name.to_string());
+    assert!(ident("a").same_kind(&ident("b")));
+    assert!(Token::Integer(1).same_kind(&Token::Integer(2)));
+    assert!(Token::Str("a".to_string()).same_kind(&Token::Str(String::new())));
+    assert!(Token::Plus.same_kind(&Token::Plus));
+    assert!(!ident("a").same_kind(&Token::Str("a".to_string())));
+    assert!(!Token::Integer(1).same_kind(&Token::Float(1.0)));
+    assert!(!Token::Lt.same_kind(&Token::Le));
+    assert_eq!(ident("a").kind(), TokenKind::Ident);
+    assert_eq!(Token::Float(1.5).kind(), TokenKind::Float);
+    assert_eq!(Token::Comment("// c".to_string()).kind_name(), "Comment");
+    assert_eq!(Token::EqEq.kind_name(), "EqEq");
+}
+
+// the binary operators only, from || to * and /
+#[test]
+fn infix_precedence() {
+    for (t, precedence) in [
+        (Token::OrOr, 1),
+        (Token::AndAnd, 2),
+        (Token::EqEq, 3),
+        (Token::NotEq, 3),
+        (Token::Lt, 3),
+        (Token::Ge, 3),
+        (Token::Plus, 4),
+        (Token::Minus, 4),
+        (Token::Star, 5),
+        (Token::Slash, 5),
+    ] {
+        assert_eq!(t.infix_precedence(), Some(precedence), "{:?}", t);
+    }
+    for t in [Token::Equal, Token::LParen, Token::Colon, Token::Integer(1), Token::Eof] {
+        assert_eq!(t.infix_precedence(), None, "{:?}", t);
+    }
+}
@@ -0,0 +1,12 @@
+use mpl2::lexer::Lexer;
+
+#[test]
+fn numbers_right_aligned() {
+    let stream = Lexer::tokenize_source("fn main() {\n    println 1 + 2\n}\n", "ts.mpl").unwrap();
+    assert_eq!(stream.tokens.len(), 11);
+    let text = stream.to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], " 1 -> ts.mpl:1:1 [Fn]");
+    assert_eq!(lines[9], "10 -> ts.mpl:3:1 [RBrace]");
+    assert!(lines.iter().all(|line| line.find(" -> ") == Some(2)), "{}", text);
+}
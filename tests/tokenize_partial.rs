@@ -0,0 +1,28 @@
+use std::rc::Rc;
+
+use mpl2::import::MemoryResolver;
+use mpl2::lexer::Lexer;
+use mpl2::token::Token;
+
+fn lexer(src: &str) -> Lexer {
+    let mut files = MemoryResolver::new();
+    files.insert("main.mpl", src);
+    Lexer::with_resolver("main.mpl".to_string(), Rc::new(files))
+}
+
+#[test]
+fn tokens_before_the_error_are_kept() {
+    let (stream, error) = lexer("fn main() {\n    let x = 1\n    println \"x\n}\n").tokenize_partial();
+    let error = error.expect("the string is not closed");
+    assert_eq!(error.message, "Unclosed string");
+    let tokens: Vec<Token> = stream.tokens.iter().map(|t| t.token.clone()).collect();
+    assert_eq!(tokens[..5], [Token::Fn, Token::Main, Token::LParen, Token::RParen, Token::LBrace]);
+    assert_eq!(tokens[tokens.len() - 2..], [Token::Println, Token::Eof]);
+}
+
+#[test]
+fn whole_file_without_error() {
+    let (stream, error) = lexer("fn main() {\n}\n").tokenize_partial();
+    assert!(error.is_none());
+    assert_eq!(stream.tokens.last().map(|t| &t.token), Some(&Token::Eof));
+}
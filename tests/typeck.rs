@@ -0,0 +1,142 @@
+mod common;
+
+use mpl2::ast::{NodeId, Program, StmtKind, Type};
+use mpl2::lexer::Lexer;
+use mpl2::parser::Parser;
+use mpl2::run_str;
+use mpl2::typeck::{TypeChecker, TypeError};
+
+use common::{mpl, write_files};
+
+// parsed without any check
+fn program(src: &str) -> Program {
+    Parser::from_stream(Lexer::tokenize_source(src, "t.mpl").unwrap()).parse_tokens().unwrap()
+}
+
+// check a program whose main has the given body
+fn check(body: &str, strict: bool) -> Result<(), TypeError> {
+    let program = program(&format!("fn main() {{\n{}\n}}\n", body));
+    TypeChecker::new(strict).check(&program)
+}
+
+fn type_error(body: &str, strict: bool) -> String {
+    check(body, strict).unwrap_err().message
+}
+
+#[test]
+fn well_typed_program() {
+    let src = "fn area(w: float, h: float) {\n    println w * h\n}\nfn main() {\n    let n = 3\n    let x: float = 2\n    call area(x, n + 1)\n    println n / 2, x / 2, to_str(n) == \"3\", [1, 2][n - 3]\n}\n";
+    assert!(TypeChecker::new(false).check(&program(src)).is_ok());
+}
+
+#[test]
+fn type_errors() {
+    for (body, message) in [
+        ("    let x = 1 + \"a\"", "can't use int and string in an arithmetic operation"),
+        ("    let x: int = 1.5", "expected int, found float"),
+        ("    if 1 {\n    }", "condition must be a bool, found int"),
+        ("    println -true", "can't negate a bool"),
+        ("    println 1 && true", "can't use int and bool in a logical operation"),
+        ("    println [1, true]", "array elements must have the same type, found int and bool"),
+        ("    println 1[0]", "can't index a int"),
+        ("    println len(1)", "can't take the length of a int"),
+    ] {
+        assert_eq!(type_error(body, false), message, "{}", body);
+    }
+    assert_eq!(check("    let x = 1 + \"a\"", false).unwrap_err().pos.col, 15);
+}
+
+// an int is promoted to a float, unless strict
+#[test]
+fn strict_rejects_the_promotion() {
+    for body in ["    println 1 + 2.5", "    let x: float = 2", "    println 2.5 * 2", "    println [1, 1.5]"] {
+        assert!(check(body, false).is_ok(), "{}", body);
+    }
+    assert_eq!(type_error("    println 1 + 2.5", true), "mixed int and float arithmetic, convert one operand explicitly");
+    assert_eq!(type_error("    let x: float = 2", true), "expected float, found int");
+    assert_eq!(type_error("    println [1, 1.5]", true), "mixed int and float arithmetic, convert one operand explicitly");
+    assert!(check("    println 1 as float + 2.5", true).is_ok());
+}
+
+#[test]
+fn strict_option() {
+    let path = write_files("strict", &[("main.mpl", "fn main() {\n    println 1 + 2.5\n}\n")]);
+    assert_eq!(mpl(&[&path], ""), (true, "3.5\n".to_string(), String::new()));
+    let (ok, out, err) = mpl(&["--strict", &path], "");
+    assert!(!ok);
+    assert_eq!(out, "");
+    assert_eq!(err, format!("Type error : [mixed int and float arithmetic, convert one operand explicitly] at {} (2:15)\n\n", path));
+}
+
+// (expr : type), an int is promoted to a float like in a declaration
+#[test]
+fn ascription() {
+    let program = program("fn main() {\n    println (1 : float), (2 * 3 : int) : 1\n}\n");
+    assert_eq!(program.to_sexpr(), "(fn main () (println (: 1 float) (: (* 2 3) int) (precision 1)))");
+    assert!(TypeChecker::new(false).check(&program).is_ok());
+    assert_eq!(run_str("fn main() {\n    println (1 : float), (2 : int)\n}\n", "t.mpl").unwrap(), "1.0 2\n");
+    assert_eq!(type_error("    println (1.5 : int)", false), "expected int, found float");
+    assert_eq!(check("    println (1.5 : int)", false).unwrap_err().pos.col, 14);
+    assert_eq!(type_error("    let a = ([1] : [float])", false), "expected [float], found [int]");
+    assert_eq!(type_error("    println (1 : float)", true), "expected float, found int");
+}
+
+const SUBTRACT: &str = "fn sub(a: int, b: int) {\n    println a - b\n}\n";
+
+// a call of sub with the given arguments
+fn call_sub(args: &str) -> Program {
+    program(&format!("{}fn main() {{\n    call sub({})\n}}\n", SUBTRACT, args))
+}
+
+// the named arguments go to their parameter whatever their order, after the positional ones
+#[test]
+fn named_arguments() {
+    assert_eq!(call_sub("b: 2, a: 1").functions[1].to_sexpr(), "(fn main () (call sub (arg b 2) (arg a 1)))");
+    for args in ["a: 1, b: 2", "b: 2, a: 1", "1, b: 2", "1, 2"] {
+        assert!(TypeChecker::new(false).check(&call_sub(args)).is_ok(), "{}", args);
+        let src = format!("{}fn main() {{\n    call sub({})\n}}\n", SUBTRACT, args);
+        assert_eq!(run_str(&src, "t.mpl").unwrap(), "-1\n", "{}", args);
+    }
+    for (args, message) in [
+        ("c: 1, b: 2", "function sub has no parameter c"),
+        ("a: 1, a: 2", "parameter a given twice"),
+        ("1, a: 2", "parameter a given twice"),
+    ] {
+        let e = TypeChecker::new(false).check(&call_sub(args)).unwrap_err();
+        assert_eq!((e.message.as_str(), e.pos.line, e.pos.col), (message, 5, 5), "{}", args);
+    }
+    let src = format!("{}fn main() {{\n    call sub(a: 1, 2)\n}}\n", SUBTRACT);
+    let e = Parser::from_stream(Lexer::tokenize_source(&src, "t.mpl").unwrap()).parse_tokens().unwrap_err();
+    assert_eq!(e.to_string(), "Grammar error : positional argument after a named argument at t.mpl line:col -> (5:20)\n");
+}
+
+// the rows of a matrix have the same length in strict mode, the error is at the first row
+// of another length
+#[test]
+fn ragged_matrix_rejected_in_strict_mode() {
+    assert!(check("    let m = [[1, 2], [3, 4]]\n    println m[1][0]", true).is_ok());
+    for body in ["    let m = [[1, 2], [3]]", "    println [[1], [2, 3]][1][0]"] {
+        assert!(check(body, false).is_ok(), "{}", body);
+    }
+    let e = check("    let m = [[1, 2], [3, 4], [5]]", true).unwrap_err();
+    assert_eq!((e.message.as_str(), e.pos.line, e.pos.col), ("ragged array, rows of 2 and 1 elements", 2, 30));
+    assert_eq!(type_error("    println [[1], [2, 3]][1][0]", true), "ragged array, rows of 1 and 2 elements");
+    assert_eq!(run_str("fn main() {\n    println [[1, 2], [3]]\n}\n", "t.mpl").unwrap(), "[[1, 2], [3]]\n");
+}
+
+#[test]
+fn expression_types_kept_by_node_id() {
+    let program = program("fn main() {\n    let x: float = 2\n    let v: [int] = []\n    println x / 2, to_str(x)\n}\n");
+    let mut checker = TypeChecker::new(false);
+    checker.check(&program).unwrap();
+    let mut types = Vec::new();
+    for stmt in &program.functions[0].body {
+        match &stmt.kind {
+            StmtKind::Let { value: Some(value), .. } => types.push(checker.type_of(value.id).cloned()),
+            StmtKind::Print { args, .. } => types.extend(args.iter().map(|arg| checker.type_of(arg.id).cloned())),
+            _ => {}
+        }
+    }
+    assert_eq!(types, [Some(Type::Int), Some(Type::Array(Box::new(Type::Int))), Some(Type::Float), Some(Type::Str)]);
+    assert_eq!(checker.type_of(NodeId(program.node_count)), None);
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{mpl, write_files};
+
+// the line count comes from the text, multi-byte chars and a missing final line break included
+#[test]
+fn summary_counts_lines_of_non_ascii_text() {
+    let path = write_files("summary", &[("main.mpl", "// déjà vu, ça marche\nfn main() {\n    println \"héhé\"\n}")]);
+    let (ok, out, err) = mpl(&["-v", &path], "");
+    assert!(ok);
+    assert_eq!(out, "héhé\n");
+    assert!(err.lines().any(|l| l == "tokenized 8 tokens across 4 lines from 1 files"), "{}", err);
+}
+
+const IMPORTING: &[(&str, &str)] = &[
+    ("main.mpl", "import \"u.mpl\"\nfn main() {\n    call g()\n    println 1\n}\n"),
+    ("u.mpl", "fn g() {\n}\n"),
+];
+
+// the phases on stderr, the output of the program is unchanged
+#[test]
+fn verbose_logs_the_phases() {
+    let path = write_files("verbose", IMPORTING);
+    let u = path.replace("main.mpl", "u.mpl");
+    let (ok, out, err) = mpl(&["-v", &path], "");
+    assert!(ok);
+    assert_eq!(out, "1\n");
+    let expected = format!(
+        "lexing {}\nlexing {}\ntokenized 18 tokens across 7 lines from 2 files\nparsing 19 tokens\nresolving names\nchecking types\nlinting\nrunning main\n",
+        path, u
+    );
+    assert_eq!(err, expected);
+}
+
+// -vv also logs the import resolution steps
+#[test]
+fn very_verbose_logs_the_imports() {
+    let path = write_files("very_verbose", IMPORTING);
+    let u = path.replace("main.mpl", "u.mpl");
+    let (ok, out, err) = mpl(&["-vv", &path], "");
+    assert!(ok);
+    assert_eq!(out, "1\n");
+    let expected = format!(
+        "lexing {0}\nimport u.mpl resolved to {1}\nlexing {1}\nsplicing 6 tokens at index 0\ntokenized 18 tokens across 7 lines from 2 files\nparsing 19 tokens\nresolving names\nchecking types\nlinting\nrunning main\n",
+        path, u
+    );
+    assert_eq!(err, expected);
+    assert_eq!(mpl(&[&path], ""), (true, "1\n".to_string(), String::new()));
+}